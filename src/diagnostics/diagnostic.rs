@@ -0,0 +1,184 @@
+use crate::interpreter::evaluator::RuntimeError;
+use crate::lexer::token::{Span, Token};
+use crate::parser::error::{ParseError, ParseErrorKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+// Which part of the pipeline produced the diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Lexer,
+    Parser,
+    Resolver,
+    Runtime,
+}
+
+impl Stage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Lexer => "lexer",
+            Stage::Parser => "parser",
+            Stage::Resolver => "resolver",
+            Stage::Runtime => "runtime",
+        }
+    }
+}
+
+// A single problem to report to the user, independent of how it gets rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub stage: Stage,
+    pub message: String,
+    pub span: Option<Span>, // None when we don't know where in the source it happened
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(stage: Stage, message: impl Into<String>, span: Option<Span>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            stage,
+            message: message.into(),
+            span,
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn from_parse_error(error: &ParseError) -> Self {
+        if error.kind == ParseErrorKind::LexError {
+            return Diagnostic::error(Stage::Lexer, error.message.clone(), Some(error.span));
+        }
+
+        let stage = match error.kind {
+            ParseErrorKind::AssignToConstant | ParseErrorKind::MisplacedPub => Stage::Resolver,
+            _ => Stage::Parser,
+        };
+        let mut diagnostic = Diagnostic::error(stage, error.message.clone(), Some(error.span));
+        if let Some(found) = &error.found {
+            diagnostic = diagnostic.with_note(format!("found {}", describe_token(found)));
+        }
+        if !error.expected.is_empty() {
+            let expected: Vec<String> = error.expected.iter().map(describe_token).collect();
+            diagnostic = diagnostic.with_note(format!("expected {}", expected.join(" or ")));
+        }
+        match error.kind {
+            ParseErrorKind::InvalidAssignmentTarget => {
+                diagnostic.with_help("only variables, list elements and map entries can appear on the left of '='")
+            }
+            ParseErrorKind::UnexpectedEof => {
+                diagnostic.with_help("the source ended early; check for a missing ')', '}' or ';'")
+            }
+            ParseErrorKind::AssignToConstant => diagnostic.with_help(CONST_HELP),
+            ParseErrorKind::OutsideLoop => {
+                diagnostic.with_help("'break' and 'continue' only work in 'while' and 'for' loops; use 'return' to leave a function")
+            }
+            _ => diagnostic,
+        }
+    }
+
+    // `span` is usually `Evaluator::last_error_span()`.
+    pub fn from_runtime_error(error: &RuntimeError, span: Option<Span>) -> Self {
+        let diagnostic = Diagnostic::error(Stage::Runtime, error.to_string(), span);
+        match error {
+            RuntimeError::UndefinedVariable(_) => {
+                diagnostic.with_help("declare the variable with 'let' before using it")
+            }
+            RuntimeError::DivisionByZero => diagnostic.with_note("the right-hand side evaluated to 0"),
+            RuntimeError::IntegerTooLarge(_) => {
+                diagnostic.with_help("use a float (e.g. '10.0') if an approximate result is fine")
+            }
+            RuntimeError::AssignToConstant { .. } => diagnostic.with_help(CONST_HELP),
+            RuntimeError::PrivateMember { .. } => {
+                diagnostic.with_help("mark the declaration 'pub' in the module to export it")
+            }
+            RuntimeError::ImportCycle(_) => {
+                diagnostic.with_help("move the code both files need into a third file that they each import")
+            }
+            RuntimeError::StackOverflow(_) => {
+                diagnostic.with_help("check that the recursion has a base case it actually reaches")
+            }
+            _ => diagnostic,
+        }
+    }
+}
+
+const CONST_HELP: &str = "declare it with 'let' instead of 'const' if it needs to change";
+
+// How a token is named in diagnostics, e.g. `';'` or `identifier`.
+fn describe_token(token: &Token) -> String {
+    let text = match token {
+        Token::LeftParen => "(",
+        Token::RightParen => ")",
+        Token::LeftBrace => "{",
+        Token::RightBrace => "}",
+        Token::LeftBracket => "[",
+        Token::RightBracket => "]",
+        Token::Comma => ",",
+        Token::Colon => ":",
+        Token::Dot => ".",
+        Token::DotDot => "..",
+        Token::DotDotEqual => "..=",
+        Token::Minus => "-",
+        Token::Plus => "+",
+        Token::Semicolon => ";",
+        Token::Slash => "/",
+        Token::Star => "*",
+        Token::Percent => "%",
+        Token::Caret => "^",
+        Token::Ampersand => "&",
+        Token::Pipe => "|",
+        Token::Equal => "=",
+        Token::PlusEqual => "+=",
+        Token::MinusEqual => "-=",
+        Token::StarEqual => "*=",
+        Token::SlashEqual => "/=",
+        Token::PercentEqual => "%=",
+        Token::BangEqual => "!=",
+        Token::EqualEqual => "==",
+        Token::FatArrow => "=>",
+        Token::Greater => ">",
+        Token::GreaterEqual => ">=",
+        Token::Less => "<",
+        Token::LessEqual => "<=",
+        Token::LessLess => "<<",
+        Token::GreaterGreater => ">>",
+        Token::Tilde => "~",
+        Token::TildeEqual => "~=",
+        Token::Identifier(name) if name.is_empty() => return "identifier".to_string(),
+        Token::Identifier(name) => return format!("identifier '{}'", name),
+        Token::StringLiteral(_) => return "string literal".to_string(),
+        Token::Template(_) => return "template string".to_string(),
+        Token::Integer(_) | Token::BigInteger(_) | Token::Number(_) => return "number".to_string(),
+        Token::Comment(_) => return "comment".to_string(),
+        Token::Error(_) => return "invalid token".to_string(),
+        Token::EOF => return "end of file".to_string(),
+        keyword => return format!("keyword '{}'", format!("{:?}", keyword).to_lowercase()),
+    };
+    format!("'{}'", text)
+}
@@ -0,0 +1,5 @@
+pub mod diagnostic;
+pub mod render;
+
+pub use diagnostic::{Diagnostic, Severity, Stage};
+pub use render::{render_json, render_plain};
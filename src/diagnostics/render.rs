@@ -0,0 +1,146 @@
+use crate::diagnostics::diagnostic::Diagnostic;
+use crate::lexer::token::Span;
+
+// Renders a diagnostic for humans, e.g.
+//
+//   error[parser]: Expected ';' after variable declaration.
+//    --> script.fddl:2:1
+//     |
+//   2 | print x;
+//     | ^^^^^
+//     = note: found keyword 'print'
+pub fn render_plain(diagnostic: &Diagnostic, source: &str, file_name: &str) -> String {
+    let mut out = format!(
+        "{}[{}]: {}\n",
+        diagnostic.severity.as_str(),
+        diagnostic.stage.as_str(),
+        diagnostic.message
+    );
+
+    let snippet = diagnostic.span.and_then(|span| Snippet::new(source, span));
+    let gutter_width = snippet.as_ref().map_or(1, |s| s.line_number.to_string().len());
+    let pad = " ".repeat(gutter_width);
+
+    match (&diagnostic.span, &snippet) {
+        (Some(span), Some(snippet)) => {
+            out.push_str(&format!("{}--> {}:{}:{}\n", pad, file_name, span.line, span.column));
+            out.push_str(&format!("{} |\n", pad));
+            out.push_str(&format!("{} | {}\n", snippet.line_number, snippet.line_text));
+            out.push_str(&format!(
+                "{} | {}{}\n",
+                pad,
+                snippet.caret_indent,
+                "^".repeat(snippet.caret_len)
+            ));
+        }
+        (Some(span), None) => {
+            out.push_str(&format!("{}--> {}:{}:{}\n", pad, file_name, span.line, span.column));
+        }
+        _ => {
+            out.push_str(&format!("{}--> {}\n", pad, file_name));
+        }
+    }
+
+    for note in &diagnostic.notes {
+        out.push_str(&format!("{} = note: {}\n", pad, note));
+    }
+    if let Some(help) = &diagnostic.help {
+        out.push_str(&format!("{} = help: {}\n", pad, help));
+    }
+    out
+}
+
+// Renders diagnostics as a JSON array, one object per diagnostic, for CI and editors.
+pub fn render_json(diagnostics: &[Diagnostic], file_name: &str) -> String {
+    let objects: Vec<String> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let (line, column, start, end) = match diagnostic.span {
+                Some(span) => (
+                    span.line.to_string(),
+                    span.column.to_string(),
+                    span.start.to_string(),
+                    span.end.to_string(),
+                ),
+                None => ("null".into(), "null".into(), "null".into(), "null".into()),
+            };
+            let notes: Vec<String> = diagnostic.notes.iter().map(|note| json_string(note)).collect();
+            let help = diagnostic.help.as_deref().map_or("null".to_string(), json_string);
+
+            format!(
+                "{{\"severity\":{},\"stage\":{},\"message\":{},\"file\":{},\"line\":{},\"column\":{},\"start\":{},\"end\":{},\"notes\":[{}],\"help\":{}}}",
+                json_string(diagnostic.severity.as_str()),
+                json_string(diagnostic.stage.as_str()),
+                json_string(&diagnostic.message),
+                json_string(file_name),
+                line,
+                column,
+                start,
+                end,
+                notes.join(","),
+                help
+            )
+        })
+        .collect();
+    format!("[{}]", objects.join(","))
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// The source line a span starts on, plus where to draw the carets under it.
+struct Snippet<'a> {
+    line_number: usize,
+    line_text: &'a str,
+    caret_indent: String,
+    caret_len: usize,
+}
+
+impl<'a> Snippet<'a> {
+    fn new(source: &'a str, span: Span) -> Option<Self> {
+        if span.start > source.len() || !source.is_char_boundary(span.start) {
+            return None;
+        }
+
+        let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[span.start..]
+            .find('\n')
+            .map_or(source.len(), |i| span.start + i);
+        let line_text = source[line_start..line_end].trim_end_matches('\r');
+
+        // Keep tabs in the indent so the carets line up with the text above them.
+        let caret_indent: String = source[line_start..span.start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        // Spans that run over several lines are underlined up to the end of the first one.
+        let underline_end = span.end.clamp(span.start, line_end);
+        let caret_len = source
+            .get(span.start..underline_end)
+            .map_or(1, |text| text.chars().count())
+            .max(1);
+
+        Some(Snippet {
+            line_number: span.line,
+            line_text,
+            caret_indent,
+            caret_len,
+        })
+    }
+}
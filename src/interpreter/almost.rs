@@ -0,0 +1,89 @@
+// Behaviour of the unary `~` ("Almost") operator on numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AlmostMode {
+    // Perturbation derived from the number's bits: always the same for the same input.
+    #[default]
+    BitHash,
+    // Random jitter of up to `magnitude` times the number, from a PRNG started at `seed`.
+    // The same seed gives the same sequence of results.
+    Seeded { seed: u64, magnitude: f64 },
+    // `~n` is just `n`. Handy for tests.
+    Off,
+}
+
+// Applies `~` to numbers according to an `AlmostMode`, keeping the PRNG state between calls.
+#[derive(Debug, Clone)]
+pub struct Almost {
+    mode: AlmostMode,
+    rng: SplitMix64,
+}
+
+impl Default for Almost {
+    fn default() -> Self {
+        Almost::new(AlmostMode::default())
+    }
+}
+
+impl Almost {
+    pub fn new(mode: AlmostMode) -> Self {
+        let seed = match mode {
+            AlmostMode::Seeded { seed, .. } => seed,
+            _ => 0,
+        };
+        Almost { mode, rng: SplitMix64::new(seed) }
+    }
+
+    pub fn mode(&self) -> AlmostMode {
+        self.mode
+    }
+
+    pub fn apply(&mut self, n: f64) -> f64 {
+        match self.mode {
+            AlmostMode::BitHash => bit_hash(n),
+            AlmostMode::Seeded { magnitude, .. } => {
+                // Zero has no scale of its own, so it jitters by up to `magnitude` itself.
+                let scale = if n == 0.0 { 1.0 } else { n.abs() };
+                let jitter = self.rng.next_f64() * 2.0 - 1.0; // In [-1, 1)
+                n + jitter * magnitude * scale
+            }
+            AlmostMode::Off => n,
+        }
+    }
+}
+
+fn bit_hash(n: f64) -> f64 {
+    if n == 0.0 {
+        return 0.1337; // Arbitrary small chaotic number
+    }
+    let bits = n.to_bits();
+    let offset_seed = (bits >> 16) & 0xFFF;
+    let scale_seed = bits & 0xFFF;
+    let chaotic_offset = (offset_seed as f64 / 4095.0 - 0.5) * n.abs() * 0.2;
+    let chaotic_scale = 1.0 + (scale_seed as f64 / 4095.0 - 0.5) * 0.1;
+    (n + chaotic_offset) * chaotic_scale
+}
+
+// Small, fast PRNG (SplitMix64). Not suitable for anything security related.
+#[derive(Debug, Clone)]
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
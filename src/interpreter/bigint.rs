@@ -0,0 +1,453 @@
+use std::cmp::Ordering;
+
+// Integers of any size, for results that don't fit in an i64. Only the operations
+// fddl's arithmetic needs are here; numeric.rs decides when to use them.
+//
+// The magnitude is stored as base-2^32 digits, least significant first, with no
+// leading zero digits, so zero is an empty list and is never negative. That makes
+// the derived equality and hash line up with the numeric value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn from_i64(n: i64) -> Self {
+        BigInt::new(n < 0, from_u64(n.unsigned_abs()))
+    }
+
+    // None unless `n` is finite and whole.
+    pub fn from_f64(n: f64) -> Option<Self> {
+        if !n.is_finite() || n.fract() != 0.0 {
+            return None;
+        }
+        if n == 0.0 {
+            return Some(BigInt::from_i64(0));
+        }
+        // A whole, non-zero float is normal: mantissa * 2^(exponent - 1075)
+        let bits = n.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64 - 1075;
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let magnitude = if exponent >= 0 {
+            shift_left(&from_u64(mantissa), exponent as u64)
+        } else {
+            from_u64(mantissa >> -exponent) // Exact, since n is whole
+        };
+        Some(BigInt::new(n < 0.0, magnitude))
+    }
+
+    // Digits with an optional leading '-', in decimal or after a `0x`, `0b` or `0o`
+    // prefix. No underscores; the lexer strips those from literals.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(text) => (true, text),
+            None => (false, text),
+        };
+        let (radix, digits) = match text.get(..2) {
+            Some("0x" | "0X") => (16, &text[2..]),
+            Some("0b" | "0B") => (2, &text[2..]),
+            Some("0o" | "0O") => (8, &text[2..]),
+            _ => (10, text),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        // Take as many digits at a time as fit in a u32: 9 in decimal, 7 in hex
+        let chunk_length = (1..).take_while(|&n| (radix as u64).pow(n) <= u32::MAX as u64).last()?;
+        let mut magnitude = Vec::new();
+        for chunk in digits.as_bytes().chunks(chunk_length as usize) {
+            let chunk = std::str::from_utf8(chunk).ok()?;
+            let value = u32::from_str_radix(chunk, radix).ok()?;
+            magnitude = mul_small_add(&magnitude, radix.pow(chunk.len() as u32), value);
+        }
+        Some(BigInt::new(negative, magnitude))
+    }
+
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        trim(&mut magnitude);
+        let negative = negative && !magnitude.is_empty();
+        BigInt { negative, magnitude }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let magnitude = self.magnitude.iter().rev().fold(0u64, |acc, &digit| (acc << 32) | digit as u64);
+        if self.negative {
+            // i64::MIN's magnitude is one more than i64::MAX
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    // The nearest float (ties to even), or an infinity for numbers beyond the float range.
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = round_to_f64(&self.magnitude, false, 0);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    // `self / other` as the nearest float. Dividing exactly first keeps this right
+    // even when the operands themselves are too big for a float, e.g. 10^400 / 10^399.
+    // Panics if `other` is zero.
+    pub fn div_to_f64(&self, other: &BigInt) -> f64 {
+        // Scale so the quotient has at least 65 bits: enough to round from, with the
+        // remainder telling whether anything was left over below them
+        let shift = 65 + other.bits() as i64 - self.bits() as i64;
+        let (quotient, remainder) = if shift >= 0 {
+            div_rem_magnitudes(&shift_left(&self.magnitude, shift as u64), &other.magnitude)
+        } else {
+            div_rem_magnitudes(&self.magnitude, &shift_left(&other.magnitude, -shift as u64))
+        };
+        let magnitude = round_to_f64(&quotient, !remainder.is_empty(), -shift);
+        if self.negative != other.negative { -magnitude } else { magnitude }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    // How many bits the magnitude takes up.
+    pub fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(top) => self.magnitude.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn neg(&self) -> Self {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> Self {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitudes(&self.magnitude, &other.magnitude));
+        }
+        // Opposite signs: the larger magnitude wins and keeps its sign
+        match compare_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::new(other.negative, sub_magnitudes(&other.magnitude, &self.magnitude)),
+            _ => BigInt::new(self.negative, sub_magnitudes(&self.magnitude, &other.magnitude)),
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> Self {
+        BigInt::new(self.negative != other.negative, mul_magnitudes(&self.magnitude, &other.magnitude))
+    }
+
+    // Division rounding towards zero, like i64's `/` and `%`: the remainder has the
+    // sign of `self`. Panics if `other` is zero.
+    pub fn div_rem(&self, other: &BigInt) -> (BigInt, BigInt) {
+        let (quotient, remainder) = div_rem_magnitudes(&self.magnitude, &other.magnitude);
+        (
+            BigInt::new(self.negative != other.negative, quotient),
+            BigInt::new(self.negative, remainder),
+        )
+    }
+
+    // Division rounding down, like fddl's `div`. Panics if `other` is zero.
+    pub fn div_floor(&self, other: &BigInt) -> BigInt {
+        let (quotient, remainder) = self.div_rem(other);
+        if !remainder.is_zero() && self.negative != other.negative {
+            quotient.sub(&BigInt::from_i64(1))
+        } else {
+            quotient
+        }
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base);
+            }
+        }
+        result
+    }
+
+    // Multiplies by 2^bits.
+    pub fn shl(&self, bits: u64) -> BigInt {
+        BigInt::new(self.negative, shift_left(&self.magnitude, bits))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Peel off nine decimal digits at a time, least significant first
+        let mut chunks = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = div_rem_small(&magnitude, 1_000_000_000);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+// --- Magnitude arithmetic ---
+// These work on unsigned digit lists, least significant first.
+
+fn from_u64(n: u64) -> Vec<u32> {
+    let mut digits = vec![n as u32, (n >> 32) as u32];
+    trim(&mut digits);
+    digits
+}
+
+fn trim(digits: &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &digit) in long.iter().enumerate() {
+        let sum = digit as u64 + short.get(i).copied().unwrap_or(0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+// `a - b`, where `a` is at least `b`.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let difference = digit as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        result.push(difference as u32); // Wraps into range when negative
+        borrow = i64::from(difference < 0);
+    }
+    trim(&mut result);
+    result
+}
+
+fn mul_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let product = x as u64 * y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    trim(&mut result);
+    result
+}
+
+fn mul_small_add(a: &[u32], factor: u32, addend: u32) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = addend as u64;
+    for &digit in a {
+        let product = digit as u64 * factor as u64 + carry;
+        result.push(product as u32);
+        carry = product >> 32;
+    }
+    result.push(carry as u32);
+    trim(&mut result);
+    result
+}
+
+fn div_rem_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for (i, &digit) in a.iter().enumerate().rev() {
+        let current = (remainder << 32) | digit as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    trim(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+fn shift_left(a: &[u32], bits: u64) -> Vec<u32> {
+    if a.is_empty() {
+        return Vec::new();
+    }
+    let (digits, bits) = ((bits / 32) as usize, (bits % 32) as u32);
+    let mut result = vec![0u32; digits];
+    let mut carry = 0u32;
+    for &digit in a {
+        result.push((digit << bits) | carry);
+        // `>> 32` would overflow, so a zero shift carries nothing
+        carry = if bits == 0 { 0 } else { digit >> (32 - bits) };
+    }
+    result.push(carry);
+    trim(&mut result);
+    result
+}
+
+// Long division (Knuth's Algorithm D): one quotient digit per step, estimated from
+// the top digits and corrected at most twice. Panics if `v` is zero.
+fn div_rem_magnitudes(u: &[u32], v: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    assert!(!v.is_empty(), "division by zero");
+    if compare_magnitudes(u, v) == Ordering::Less {
+        return (Vec::new(), u.to_vec());
+    }
+    if v.len() == 1 {
+        let (quotient, remainder) = div_rem_small(u, v[0]);
+        return (quotient, from_u64(remainder as u64));
+    }
+
+    // Shift both so the divisor's top digit has its high bit set, which keeps the
+    // estimates close. The shift doesn't change the quotient.
+    let shift = v[v.len() - 1].leading_zeros() as u64;
+    let v = shift_left(v, shift);
+    let mut u = shift_left(u, shift);
+    let n = v.len();
+    u.resize(u.len().max(n) + 1, 0);
+    let m = u.len() - 1 - n;
+
+    let base = 1u64 << 32;
+    let mut quotient = vec![0u32; m + 1];
+    for j in (0..=m).rev() {
+        let top = ((u[j + n] as u64) << 32) | u[j + n - 1] as u64;
+        let mut estimate = top / v[n - 1] as u64;
+        let mut remainder = top % v[n - 1] as u64;
+        while estimate >= base || estimate * v[n - 2] as u64 > ((remainder << 32) | u[j + n - 2] as u64) {
+            estimate -= 1;
+            remainder += v[n - 1] as u64;
+            if remainder >= base {
+                break;
+            }
+        }
+
+        // u[j..=j + n] -= estimate * v
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let product = estimate * v[i] as u64 + carry;
+            carry = product >> 32;
+            let difference = u[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+            u[i + j] = difference as u32;
+            borrow = i64::from(difference < 0);
+        }
+        let difference = u[j + n] as i64 - borrow - carry as i64;
+        u[j + n] = difference as u32;
+
+        // The estimate was one too big: add the divisor back
+        if difference < 0 {
+            estimate -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                u[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            u[j + n] = u[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = estimate as u32;
+    }
+
+    trim(&mut quotient);
+    // Undo the shift on what's left over
+    let mut remainder: Vec<u32> = (0..n)
+        .map(|i| {
+            let high = if shift > 0 && i + 1 < n { u[i + 1] << (32 - shift) } else { 0 };
+            (u[i] >> shift) | high
+        })
+        .collect();
+    trim(&mut remainder);
+    (quotient, remainder)
+}
+
+// The nearest float to `magnitude * 2^exponent`, rounding ties to even. `inexact`
+// says nonzero bits below `magnitude` were already dropped, which matters when the
+// dropped part looks like an exact tie. Results in the subnormal range may be off
+// by one in the last place.
+fn round_to_f64(magnitude: &[u32], inexact: bool, exponent: i64) -> f64 {
+    let bits = match magnitude.last() {
+        Some(top) => magnitude.len() as u64 * 32 - top.leading_zeros() as u64,
+        None => return 0.0,
+    };
+    // The top 64 bits (or all of them), and whether anything below those is set
+    let dropped = bits.saturating_sub(64);
+    let (digit, offset) = ((dropped / 32) as usize, (dropped % 32) as u32);
+    let window = (0..3).fold(0u128, |acc, i| acc | (magnitude.get(digit + i).copied().unwrap_or(0) as u128) << (32 * i));
+    let mut top = (window >> offset) as u64;
+    let inexact = inexact || magnitude[..digit].iter().any(|&d| d != 0) || magnitude[digit] & ((1 << offset) - 1) != 0;
+    let mut exponent = exponent + dropped as i64;
+
+    // Keep 53 bits and round on the 11 below them
+    let zeros = top.leading_zeros();
+    top <<= zeros;
+    exponent -= zeros as i64;
+    let mut mantissa = top >> 11;
+    let rest = top & 0x7ff;
+    if rest > 0x400 || (rest == 0x400 && (inexact || mantissa & 1 == 1)) {
+        mantissa += 1;
+    }
+    scale(mantissa as f64, exponent + 11)
+}
+
+// `x * 2^exponent`, in steps so an intermediate power of two can't overflow on its own.
+fn scale(mut x: f64, mut exponent: i64) -> f64 {
+    while exponent > 1000 && x.is_finite() {
+        x *= 2f64.powi(1000);
+        exponent -= 1000;
+    }
+    while exponent < -1000 && x != 0.0 {
+        x *= 2f64.powi(-1000);
+        exponent += 1000;
+    }
+    x * 2f64.powi(exponent as i32)
+}
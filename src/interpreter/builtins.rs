@@ -0,0 +1,71 @@
+use crate::interpreter::bigint::BigInt;
+use crate::interpreter::evaluator::{FddlValue, RuntimeError};
+use crate::interpreter::numeric::Number;
+
+// Functions every program can call without declaring them. They live in the
+// top-level scope of each file, so a script can still shadow them with `let`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Builtin {
+    Int,   // int(x): a float rounded towards zero, or a string or boolean as an int
+    Float, // float(x): an int, string or boolean as a float
+}
+
+impl Builtin {
+    pub const ALL: [Builtin; 2] = [Builtin::Int, Builtin::Float];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Builtin::Int => "int",
+            Builtin::Float => "float",
+        }
+    }
+
+    pub fn arity(self) -> usize {
+        match self {
+            Builtin::Int | Builtin::Float => 1,
+        }
+    }
+
+    // The caller has already checked the argument count against `arity`.
+    pub fn call(self, arguments: Vec<FddlValue>) -> Result<FddlValue, RuntimeError> {
+        let argument = &arguments[0];
+        match self {
+            Builtin::Int => to_int(argument),
+            Builtin::Float => to_float(argument).map(FddlValue::Number),
+        }
+    }
+}
+
+fn to_int(value: &FddlValue) -> Result<FddlValue, RuntimeError> {
+    let converted = match value {
+        FddlValue::Int(_) | FddlValue::BigInt(_) => Some(value.clone()),
+        FddlValue::Number(n) => BigInt::from_f64(n.trunc()).map(|n| Number::from_big(n).to_value()),
+        FddlValue::String(s) => {
+            let s = s.trim();
+            BigInt::parse(s.strip_prefix('+').unwrap_or(s)).map(|n| Number::from_big(n).to_value())
+        }
+        FddlValue::Boolean(b) => Some(FddlValue::Int(i64::from(*b))),
+        _ => None,
+    };
+    converted.ok_or_else(|| conversion_error(value, "an int"))
+}
+
+fn to_float(value: &FddlValue) -> Result<f64, RuntimeError> {
+    let converted = match value {
+        FddlValue::Int(n) => Some(*n as f64),
+        FddlValue::BigInt(n) => Some(n.to_f64()),
+        FddlValue::Number(n) => Some(*n),
+        FddlValue::String(s) => s.trim().parse::<f64>().ok(),
+        FddlValue::Boolean(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    };
+    converted.ok_or_else(|| conversion_error(value, "a float"))
+}
+
+fn conversion_error(value: &FddlValue, target: &str) -> RuntimeError {
+    let shown = match value {
+        FddlValue::String(s) => format!("{:?}", s),
+        other => other.to_string(),
+    };
+    RuntimeError::TypeMismatch(format!("Can't convert {} to {}.", shown, target))
+}
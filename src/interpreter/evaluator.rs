@@ -1,49 +1,373 @@
-use crate::parser::ast::{Expression, Statement, Literal, Operator};
-use std::collections::HashMap;
+use crate::interpreter::almost::{Almost, AlmostMode};
+use crate::interpreter::bigint::BigInt;
+use crate::interpreter::builtins::Builtin;
+use crate::interpreter::iteration::{FddlRange, ForInCursor};
+use crate::interpreter::map::{FddlMap, MapKey};
+use crate::interpreter::numeric::{self, Number};
+use crate::interpreter::symbol::Symbol;
+use crate::lexer::Lexer;
+use crate::lexer::token::Span;
+use crate::parser::Parser;
+use crate::parser::ast::{AssignTarget, Expression, ExpressionKind, InterpolationPart, Statement, StatementKind, Literal, Operator};
+use crate::parser::resolver::resolve;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 // --- Runtime Values ---
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum FddlValue {
-    Number(f64),
+    Int(i64),
+    BigInt(Rc<BigInt>), // An int outside the i64 range; see numeric.rs
+    Number(f64), // A float
     Boolean(bool),
     String(String),
+    Symbol(Symbol),
+    List(SharedList),
+    Map(SharedMap),
+    Function(Rc<FddlFunction>),
+    Builtin(Builtin),
+    Module(Rc<FddlModule>),
+    Range(FddlRange),
     Nil,
-    // Later, you might add: Function, Array, Object/Struct, etc.
+    // Later, you might add: Array, Object/Struct, etc.
+}
+
+// Lists are shared by reference: passing one to a function or storing it in
+// another variable doesn't copy it, so changes through one name show up in all.
+pub type SharedList = Rc<RefCell<Vec<FddlValue>>>;
+
+// Maps are shared the same way.
+pub type SharedMap = Rc<RefCell<FddlMap>>;
+
+// A user-defined function, created when a `func` declaration or `func (...)`
+// expression is executed. It keeps hold of the scope it was declared in, so it
+// can still see (and modify) those variables after that scope has been exited.
+pub struct FddlFunction {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Vec<Statement>,
+    pub closure: SharedEnvironment,
+    pub file: Option<PathBuf>, // The file it was declared in, so errors inside it aren't shown against another file
+}
+
+// The closure is left out on purpose: it usually contains the function itself.
+impl std::fmt::Debug for FddlFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FddlFunction")
+            .field("name", &self.name)
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+// Functions are only equal to themselves, not to another function with the same body.
+impl PartialEq for FddlFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+// A `module` block or an imported file. Its members live in `environment`; only
+// the ones declared `pub` can be reached from outside with `module.member`.
+pub struct FddlModule {
+    pub name: String,
+    pub environment: SharedEnvironment,
+}
+
+impl std::fmt::Debug for FddlModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FddlModule")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+// Like functions, modules are only equal to themselves.
+impl PartialEq for FddlModule {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl FddlValue {
+    // What error messages call the value's type, e.g. "Got list and int".
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            FddlValue::Int(_) | FddlValue::BigInt(_) => "int",
+            FddlValue::Number(_) => "float",
+            FddlValue::Boolean(_) => "boolean",
+            FddlValue::String(_) => "string",
+            FddlValue::Symbol(_) => "symbol",
+            FddlValue::List(_) => "list",
+            FddlValue::Map(_) => "map",
+            FddlValue::Function(_) | FddlValue::Builtin(_) => "function",
+            FddlValue::Module(_) => "module",
+            FddlValue::Range(_) => "range",
+            FddlValue::Nil => "nil",
+        }
+    }
+}
+
+// Values of the same type compare as usual; the one mix that can be equal is an
+// int and a float with the same value, e.g. `1 == 1.0`.
+impl PartialEq for FddlValue {
+    fn eq(&self, other: &Self) -> bool {
+        values_equal(self, other, &mut Vec::new())
+    }
+}
+
+// Lists compare item by item and maps entry by entry, whatever order the keys
+// were added in. `comparing` holds the pairs of lists and maps already being
+// compared further up, so one that contains itself doesn't recurse forever:
+// meeting the same pair again adds nothing new, so that branch counts as equal.
+fn values_equal(left: &FddlValue, right: &FddlValue, comparing: &mut Vec<(*const (), *const ())>) -> bool {
+    if let (Some(l), Some(r)) = (Number::from_value(left), Number::from_value(right)) {
+        return numeric::compare(&l, &r) == Some(std::cmp::Ordering::Equal);
+    }
+    match (left, right) {
+        (FddlValue::List(l), FddlValue::List(r)) => {
+            let pair = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+            if Rc::ptr_eq(l, r) || comparing.contains(&pair) {
+                return true;
+            }
+            let (l, r) = (l.borrow(), r.borrow());
+            if l.len() != r.len() {
+                return false;
+            }
+            comparing.push(pair);
+            let equal = l.iter().zip(r.iter()).all(|(a, b)| values_equal(a, b, comparing));
+            comparing.pop();
+            equal
+        }
+        (FddlValue::Map(l), FddlValue::Map(r)) => {
+            let pair = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+            if Rc::ptr_eq(l, r) || comparing.contains(&pair) {
+                return true;
+            }
+            let (l, r) = (l.borrow(), r.borrow());
+            if l.len() != r.len() {
+                return false;
+            }
+            comparing.push(pair);
+            let equal = l
+                .iter()
+                .all(|(key, a)| r.get(key).is_some_and(|b| values_equal(a, b, comparing)));
+            comparing.pop();
+            equal
+        }
+        (FddlValue::Boolean(l), FddlValue::Boolean(r)) => l == r,
+        (FddlValue::String(l), FddlValue::String(r)) => l == r,
+        (FddlValue::Symbol(l), FddlValue::Symbol(r)) => l == r,
+        (FddlValue::Function(l), FddlValue::Function(r)) => l == r,
+        (FddlValue::Builtin(l), FddlValue::Builtin(r)) => l == r,
+        (FddlValue::Module(l), FddlValue::Module(r)) => l == r,
+        (FddlValue::Range(l), FddlValue::Range(r)) => l == r,
+        (FddlValue::Nil, FddlValue::Nil) => true,
+        _ => false,
+    }
 }
 
 impl std::fmt::Display for FddlValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            FddlValue::Int(n) => write!(f, "{}", n),
+            FddlValue::BigInt(n) => write!(f, "{}", n),
+            // Whole floats keep their ".0" so they can't be mistaken for ints
+            FddlValue::Number(n) if n.is_finite() && n.fract() == 0.0 => write!(f, "{:.1}", n),
             FddlValue::Number(n) => write!(f, "{}", n),
             FddlValue::Boolean(b) => write!(f, "{}", b),
             FddlValue::String(s) => write!(f, "{}", s),
+            FddlValue::Symbol(symbol) => write!(f, "{}", symbol),
+            FddlValue::List(_) | FddlValue::Map(_) => fmt_nested(self, f, &mut Vec::new()),
+            FddlValue::Function(func) => write!(f, "<func {}>", func.name),
+            FddlValue::Builtin(builtin) => write!(f, "<builtin {}>", builtin.name()),
+            FddlValue::Module(module) => write!(f, "<module {}>", module.name),
+            FddlValue::Range(range) => write!(f, "{}", range),
             FddlValue::Nil => write!(f, "nil"),
         }
     }
 }
 
+// Lists print like `[1, "two", :three]` and maps like `{"a": 1, :b: [2]}`. Strings
+// inside them are quoted, so `["a, b"]` and `["a", "b"]` look different. A list or
+// map that contains itself prints as `[...]` or `{...}` the second time round
+// instead of recursing forever.
+fn fmt_nested(
+    value: &FddlValue,
+    f: &mut std::fmt::Formatter<'_>,
+    open: &mut Vec<*const ()>,
+) -> std::fmt::Result {
+    let pointer = match value {
+        FddlValue::String(s) => return write!(f, "{:?}", s),
+        FddlValue::List(list) => Rc::as_ptr(list) as *const (),
+        FddlValue::Map(map) => Rc::as_ptr(map) as *const (),
+        other => return write!(f, "{}", other),
+    };
+    if open.contains(&pointer) {
+        return write!(f, "{}", if matches!(value, FddlValue::List(_)) { "[...]" } else { "{...}" });
+    }
+
+    open.push(pointer);
+    match value {
+        FddlValue::List(list) => {
+            write!(f, "[")?;
+            for (i, item) in list.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                fmt_nested(item, f, open)?;
+            }
+            write!(f, "]")?;
+        }
+        FddlValue::Map(map) => {
+            write!(f, "{{")?;
+            for (i, (key, item)) in map.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                fmt_nested(&key.to_value(), f, open)?;
+                write!(f, ": ")?;
+                fmt_nested(item, f, open)?;
+            }
+            write!(f, "}}")?;
+        }
+        _ => unreachable!("only lists and maps get this far"),
+    }
+    open.pop();
+    Ok(())
+}
+
+// For "must be a whole number" errors: `1.5` says more than "float" would.
+fn describe_number(value: &FddlValue) -> String {
+    match value {
+        FddlValue::Int(_) | FddlValue::BigInt(_) | FddlValue::Number(_) => value.to_string(),
+        other => other.type_name().to_string(),
+    }
+}
+
+// The longest string `*` will build (256 MiB), so a typo in a repeat count fails
+// with an error instead of taking down the interpreter when the allocation does.
+const MAX_REPEAT_BYTES: usize = 1 << 28;
+
+// How deeply function calls can nest before it's reported as a stack overflow.
+// Each fddl call is many Rust frames deep (hundreds of KB in a debug build for a
+// busy function body), so the evaluator has to run on a thread with a stack of
+// STACK_SIZE bytes for this many calls to fit.
+pub const MAX_CALL_DEPTH: usize = 2000;
+pub const STACK_SIZE: usize = 1 << 30;
+
 // --- Runtime Errors ---
 #[derive(Debug)]
 pub enum RuntimeError {
     TypeMismatch(String),
     UndefinedVariable(String),
     DivisionByZero,
-    // You could add more specific errors, e.g., IncorrectArgumentCount, etc.
+    IncorrectArgumentCount {
+        function_name: String,
+        expected: usize,
+        found: usize,
+    },
+    AssignToConstant {
+        name: String,
+        declared_at: Span,
+    },
+    IndexOutOfBounds {
+        index: i64,
+        length: usize,
+    },
+    IntegerTooLarge(String), // The operation, e.g. "10 ^ 10000000"
+    StringTooLong(String),   // The operation, e.g. "repeating a 2-byte string 1000000000 times"
+    UndefinedMember {
+        module: String,
+        member: String,
+    },
+    PrivateMember {
+        module: String,
+        member: String,
+    },
+    ImportFailed {
+        path: String,
+        message: String,
+    },
+    ImportCycle(Vec<String>), // Paths in import order, ending with the one imported again
+    StackOverflow(String),    // The function whose call went past MAX_CALL_DEPTH
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::TypeMismatch(message) => write!(f, "{}", message),
+            RuntimeError::UndefinedVariable(message) => write!(f, "{}", message),
+            RuntimeError::DivisionByZero => write!(f, "Division by zero."),
+            RuntimeError::IncorrectArgumentCount { function_name, expected, found } => write!(
+                f,
+                "Function '{}' expects {} argument(s) but was called with {}.",
+                function_name, expected, found
+            ),
+            RuntimeError::AssignToConstant { name, declared_at } => write!(
+                f,
+                "Cannot assign to constant '{}' (declared at line {}, column {}).",
+                name, declared_at.line, declared_at.column
+            ),
+            RuntimeError::IndexOutOfBounds { index, length } => write!(
+                f,
+                "Index {} is out of bounds for a list of length {}.",
+                index, length
+            ),
+            RuntimeError::IntegerTooLarge(operation) => {
+                write!(f, "The result of {} is too large to compute.", operation)
+            }
+            RuntimeError::StringTooLong(operation) => write!(
+                f,
+                "The result of {} would be longer than the {} byte limit for a string.",
+                operation, MAX_REPEAT_BYTES
+            ),
+            RuntimeError::UndefinedMember { module, member } => {
+                write!(f, "Module '{}' has no member '{}'.", module, member)
+            }
+            RuntimeError::PrivateMember { module, member } => write!(
+                f,
+                "'{}' is private to module '{}'. Only 'pub' declarations can be used from outside.",
+                member, module
+            ),
+            RuntimeError::ImportFailed { path, message } => {
+                write!(f, "Failed to import '{}': {}", path, message)
+            }
+            RuntimeError::ImportCycle(paths) => write!(f, "Import cycle: {}.", paths.join(" -> ")),
+            RuntimeError::StackOverflow(function_name) => write!(
+                f,
+                "Stack overflow: calling '{}' went more than {} calls deep.",
+                function_name, MAX_CALL_DEPTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+// --- Statement Outcomes ---
+// How control leaves a statement: normally, by unwinding out of a function with
+// `return`, or out of the innermost loop (or just its current pass) with `break`/`continue`.
+enum ControlFlow {
+    Normal,
+    Return(FddlValue),
+    Break,
+    Continue,
 }
 
 // --- Environment for Variables ---
+// Scopes are shared frames: a block, a function call and every closure created
+// inside them all hold the same frame, so a write through one is seen by all.
+pub type SharedEnvironment = Rc<RefCell<Environment>>;
+
+#[derive(Default)]
 pub struct Environment {
     values: HashMap<String, FddlValue>,
-    parent: Option<Box<Environment>>,
-}
-
-impl Default for Environment {
-    fn default() -> Self {
-        Environment {
-            values: HashMap::new(),
-            parent: None,
-        }
-    }
+    constants: HashMap<String, Span>, // Names in `values` declared with `const`, and where
+    public: HashSet<String>,          // Names in `values` declared with `pub`
+    parent: Option<SharedEnvironment>,
 }
 
 impl Environment {
@@ -52,17 +376,57 @@ impl Environment {
         Environment::default()
     }
 
+    // The top-level scope of a file: empty apart from the builtin functions.
+    pub fn global() -> Self {
+        let mut environment = Environment::new();
+        for builtin in Builtin::ALL {
+            environment.values.insert(builtin.name().to_string(), FddlValue::Builtin(builtin));
+        }
+        environment
+    }
+
     // Creates a new environment that encloses a parent environment (for new scopes)
-    pub fn new_enclosed(parent_environment: Environment) -> Self {
+    pub fn new_enclosed(parent_environment: SharedEnvironment) -> Self {
         Environment {
             values: HashMap::new(),
-            parent: Some(Box::new(parent_environment)),
+            constants: HashMap::new(),
+            public: HashSet::new(),
+            parent: Some(parent_environment),
         }
     }
 
-    // Defines a new variable in the current scope. Allows shadowing.
-    pub fn define(&mut self, name: String, value: FddlValue) {
+    // Wraps this environment up so it can be shared between scopes and closures.
+    pub fn into_shared(self) -> SharedEnvironment {
+        Rc::new(RefCell::new(self))
+    }
+
+    // Defines a new variable in the current scope. Allows shadowing, and replacing
+    // an earlier variable in this scope unless that one is a constant.
+    pub fn define(&mut self, name: String, value: FddlValue) -> Result<(), RuntimeError> {
+        if let Some(declared_at) = self.constants.get(&name) {
+            return Err(RuntimeError::AssignToConstant { name, declared_at: *declared_at });
+        }
         self.values.insert(name, value);
+        Ok(())
+    }
+
+    // Defines a variable that can't be assigned to afterwards. `declared_at` is
+    // reported if something tries.
+    pub fn define_constant(&mut self, name: String, value: FddlValue, declared_at: Span) -> Result<(), RuntimeError> {
+        self.define(name.clone(), value)?;
+        self.constants.insert(name, declared_at);
+        Ok(())
+    }
+
+    pub fn mark_public(&mut self, name: &str) {
+        self.public.insert(name.to_string());
+    }
+
+    // Looks up a variable in this scope only, along with whether it is `pub`.
+    pub fn get_member(&self, name: &str) -> Option<(FddlValue, bool)> {
+        self.values
+            .get(name)
+            .map(|value| (value.clone(), self.public.contains(name)))
     }
 
     // Gets a variable's value, looking up through parent scopes if necessary.
@@ -70,8 +434,8 @@ impl Environment {
         match self.values.get(name) {
             Some(value) => Ok(value.clone()), // Clone to return an owned value
             None => { // Not found in current scope, try parent
-                if let Some(parent_env_box) = &self.parent {
-                    parent_env_box.get(name) // Recursive call
+                if let Some(parent_env) = &self.parent {
+                    parent_env.borrow().get(name) // Recursive call
                 } else {
                     Err(RuntimeError::UndefinedVariable(format!(
                         "Undefined variable '{}'.",
@@ -85,14 +449,20 @@ impl Environment {
     // Assigns a new value to an existing variable.
     // It must exist in the current or an enclosing scope.
     pub fn assign(&mut self, name: &str, value: FddlValue) -> Result<(), RuntimeError> {
-        if self.values.contains_key(name) {
+        if let Some(slot) = self.values.get_mut(name) {
+            if let Some(declared_at) = self.constants.get(name) {
+                return Err(RuntimeError::AssignToConstant {
+                    name: name.to_string(),
+                    declared_at: *declared_at,
+                });
+            }
             // Variable exists in the current scope, assign here.
-            self.values.insert(name.to_string(), value);
+            *slot = value;
             Ok(())
         } else {
             // Not in current scope, try to assign in parent scope.
-            if let Some(parent_env_box) = &mut self.parent {
-                parent_env_box.assign(name, value) // Recursive call
+            if let Some(parent_env) = &self.parent {
+                parent_env.borrow_mut().assign(name, value) // Recursive call
             } else {
                 Err(RuntimeError::UndefinedVariable(format!(
                     "Cannot assign to undefined variable '{}' (not found in any scope).",
@@ -103,117 +473,595 @@ impl Environment {
     }
 }
 
+// How close two numbers must be for `~=` to call them equal. They match when they
+// differ by at most `absolute`, or by at most `relative` times the larger magnitude.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance { absolute: 1e-9, relative: 1e-6 }
+    }
+}
+
 // --- Evaluator ---
 pub struct Evaluator {
-    environment: Environment,
+    environment: SharedEnvironment,
+    error_span: Option<Span>, // Innermost node that raised the last runtime error
+    tolerance: Tolerance,     // Used by `~=`
+    almost: Almost,           // Used by unary `~` on numbers
+    current_file: Option<PathBuf>, // Imports are resolved relative to this file
+    modules: HashMap<PathBuf, Rc<FddlModule>>, // Imported files, so each one only runs once
+    loading: Vec<PathBuf>, // Files being run right now, starting with the main one
+    call_depth: usize,     // Function calls running right now
 }
 
 // Helper for truthiness (nil and false are falsey, everything else is truthy)
 // Defined as an associated function because it doesn't need `self`.
 impl Evaluator {
     fn is_truthy(value: &FddlValue) -> bool {
-        match value {
-            FddlValue::Boolean(false) => false,
-            FddlValue::Nil => false,
-            _ => true,
+        !matches!(value, FddlValue::Boolean(false) | FddlValue::Nil)
+    }
+}
+
+// Operator rules that go beyond plain number arithmetic.
+impl Evaluator {
+    // `+` adds numbers and concatenates strings. When one side is a string, a number or
+    // boolean on the other side is converted with its display form ("n = " + 1 is "n = 1").
+    // Other values have to be converted explicitly, e.g. with a template.
+    fn add(left: FddlValue, right: FddlValue) -> Result<FddlValue, RuntimeError> {
+        if let (Some(l), Some(r)) = (Number::from_value(&left), Number::from_value(&right)) {
+            return numeric::arithmetic(&Operator::Plus, "+", l, r);
         }
+        match (&left, &right) {
+            (FddlValue::String(l), FddlValue::String(r)) => Ok(FddlValue::String(format!("{}{}", l, r))),
+            (FddlValue::String(l), FddlValue::Int(_) | FddlValue::Number(_) | FddlValue::Boolean(_)) => {
+                Ok(FddlValue::String(format!("{}{}", l, right)))
+            }
+            (FddlValue::Int(_) | FddlValue::Number(_) | FddlValue::Boolean(_), FddlValue::String(r)) => {
+                Ok(FddlValue::String(format!("{}{}", left, r)))
+            }
+            _ => Err(RuntimeError::TypeMismatch(format!(
+                "Operands for '+' must be numbers, or a string and a string, number or boolean. Got {} and {}",
+                left.type_name(),
+                right.type_name()
+            ))),
+        }
+    }
+
+    // `"ab" * 3` is "ababab". The count must be a non-negative whole number.
+    fn repeat(text: &str, count: Number) -> Result<FddlValue, RuntimeError> {
+        let whole = match count.to_whole() {
+            Some(whole) if whole >= 0 => whole as u128,
+            // Bigger than any int: still fine for "", far too long for anything else
+            _ if matches!(&count, Number::Big(big) if !big.is_negative()) => u128::MAX,
+            _ => {
+                return Err(RuntimeError::TypeMismatch(format!(
+                    "Strings can only be repeated a non-negative whole number of times. Got {}",
+                    count.to_value()
+                )))
+            }
+        };
+        if (text.len() as u128).saturating_mul(whole) > MAX_REPEAT_BYTES as u128 {
+            return Err(RuntimeError::StringTooLong(format!(
+                "repeating a {}-byte string {} times",
+                text.len(),
+                count.to_value()
+            )));
+        }
+        Ok(FddlValue::String(text.repeat(whole as usize)))
+    }
+
+    // Numbers compare numerically (ints and floats alike) and strings lexicographically
+    // (by code point). Comparisons involving NaN are always false.
+    fn compare(
+        symbol: &str,
+        left: &FddlValue,
+        right: &FddlValue,
+        test: fn(std::cmp::Ordering) -> bool,
+    ) -> Result<FddlValue, RuntimeError> {
+        let ordering = match (left, right) {
+            (FddlValue::String(l), FddlValue::String(r)) => Some(l.cmp(r)),
+            _ if Number::from_value(left).is_some() && Number::from_value(right).is_some() => {
+                numeric::compare(&Number::from_value(left).unwrap(), &Number::from_value(right).unwrap())
+            }
+            _ => {
+                return Err(RuntimeError::TypeMismatch(format!(
+                    "Operands for '{}' must both be numbers or both be strings. Got {} and {}",
+                    symbol,
+                    left.type_name(),
+                    right.type_name()
+                )))
+            }
+        };
+        Ok(FddlValue::Boolean(ordering.is_some_and(test)))
+    }
+
+    // `~=`: numbers within the evaluator's tolerance, strings that match ignoring case
+    // and whitespace, and anything else only if it is plainly equal.
+    fn almost_equal(&self, left: &FddlValue, right: &FddlValue) -> bool {
+        if let (Some(l), Some(r)) = (Number::from_value(left), Number::from_value(right)) {
+            if left == right {
+                return true; // Also covers matching infinities and ints too big for a float
+            }
+            let (l, r) = (l.to_f64(), r.to_f64());
+            if !l.is_finite() || !r.is_finite() {
+                return false;
+            }
+            let difference = (l - r).abs();
+            let scale = l.abs().max(r.abs());
+            return difference <= self.tolerance.absolute || difference <= self.tolerance.relative * scale;
+        }
+        match (left, right) {
+            (FddlValue::String(l), FddlValue::String(r)) => {
+                let normalize = |text: &str| -> String {
+                    text.chars()
+                        .filter(|c| !c.is_whitespace())
+                        .flat_map(char::to_lowercase)
+                        .collect()
+                };
+                normalize(l) == normalize(r)
+            }
+            _ => left == right,
+        }
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Evaluator {
     pub fn new() -> Self {
         Evaluator {
-            environment: Environment::new(), // Start with a global environment
+            environment: Environment::global().into_shared(), // Start with a global environment
+            error_span: None,
+            tolerance: Tolerance::default(),
+            almost: Almost::default(),
+            current_file: None,
+            modules: HashMap::new(),
+            loading: Vec::new(),
+            call_depth: 0,
         }
     }
 
+    // The file being run. Without one, imports are resolved from the working directory.
+    pub fn set_current_file(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        // The main file counts as being loaded, so importing it again is a cycle.
+        self.loading = std::fs::canonicalize(&path).into_iter().collect();
+        self.current_file = Some(path);
+    }
+
+    pub fn tolerance(&self) -> Tolerance {
+        self.tolerance
+    }
+
+    // Changes how loosely `~=` compares numbers from now on.
+    pub fn set_tolerance(&mut self, tolerance: Tolerance) {
+        self.tolerance = tolerance;
+    }
+
+    pub fn almost_mode(&self) -> AlmostMode {
+        self.almost.mode()
+    }
+
+    // Changes how `~` perturbs numbers. Setting a seeded mode restarts its sequence.
+    pub fn set_almost_mode(&mut self, mode: AlmostMode) {
+        self.almost = Almost::new(mode);
+    }
+
+    // Looks up a variable visible from the current scope.
+    pub fn get_variable(&self, name: &str) -> Result<FddlValue, RuntimeError> {
+        self.environment.borrow().get(name)
+    }
+
+    // Source location of the innermost expression or statement that caused the most
+    // recent runtime error from `evaluate_program`.
+    pub fn last_error_span(&self) -> Option<Span> {
+        self.error_span
+    }
+
     pub fn evaluate_program(&mut self, statements: Vec<Statement>) -> Result<(), RuntimeError> {
+        self.error_span = None;
         for statement in statements {
-            self.evaluate_statement(&statement)?;
+            // A top-level `return` simply ends the program.
+            if let ControlFlow::Return(_) = self.evaluate_statement(&statement)? {
+                break;
+            }
         }
         Ok(())
     }
 
-    fn evaluate_statement(&mut self, statement: &Statement) -> Result<(), RuntimeError> {
-        match statement {
-            Statement::PrintStatement(expr) => {
+    fn evaluate_statement(&mut self, statement: &Statement) -> Result<ControlFlow, RuntimeError> {
+        let result = self.execute_statement_kind(statement);
+        if result.is_err() && self.error_span.is_none() {
+            self.error_span = Some(statement.span);
+        }
+        result
+    }
+
+    fn execute_statement_kind(&mut self, statement: &Statement) -> Result<ControlFlow, RuntimeError> {
+        match &statement.kind {
+            StatementKind::PrintStatement(expr) => {
                 let value = self.evaluate_expression(expr)?;
                 println!("{}", value);
             }
-            Statement::ExpressionStatement(expr) => {
+            StatementKind::ExpressionStatement(expr) => {
                 self.evaluate_expression(expr)?; // Evaluate for side effects, discard result
             }
-            Statement::VariableDeclaration(name, initializer) => {
+            StatementKind::VariableDeclaration(name, initializer) => {
                 let value = match initializer {
                     Some(init_expr) => self.evaluate_expression(init_expr)?,
                     None => FddlValue::Nil, // Default to nil if no initializer
                 };
-                self.environment.define(name.clone(), value);
-            }
-            Statement::Assignment { target_name, value } => {
-                let val_to_assign = self.evaluate_expression(value)?;
-                self.environment.assign(target_name, val_to_assign)?;
-            }
-            Statement::Block(statements) => {
-                // Create a new scope for the block
-                let outer_environment = std::mem::take(&mut self.environment); // Takes current, leaves Default in self.env
-                self.environment = Environment::new_enclosed(outer_environment); // New current env, old one is parent
-
-                let mut block_execution_result: Result<(), RuntimeError> = Ok(());
-                for stmt_in_block in statements {
-                    block_execution_result = self.evaluate_statement(stmt_in_block);
-                    if block_execution_result.is_err() {
-                        break; // Stop executing statements in block if one errors
+                self.environment.borrow_mut().define(name.clone(), value)?;
+            }
+            StatementKind::ModuleDeclaration { name, body } => {
+                let module_environment = Environment::new_enclosed(Rc::clone(&self.environment)).into_shared();
+                self.execute_in_environment(Rc::clone(&module_environment), |evaluator| {
+                    evaluator.execute_statements(body)
+                })?;
+                let module = FddlModule { name: name.clone(), environment: module_environment };
+                self.environment.borrow_mut().define(name.clone(), FddlValue::Module(Rc::new(module)))?;
+            }
+            StatementKind::Public(declaration) => {
+                let flow = self.evaluate_statement(declaration)?;
+                if let Some(name) = declaration.kind.declared_name() {
+                    self.environment.borrow_mut().mark_public(name);
+                }
+                return Ok(flow);
+            }
+            StatementKind::Import { path, name } => {
+                let module = self.import_module(path, name)?;
+                self.environment.borrow_mut().define(name.clone(), FddlValue::Module(module))?;
+            }
+            StatementKind::ConstDeclaration(name, value_expr) => {
+                let value = self.evaluate_expression(value_expr)?;
+                self.environment.borrow_mut().define_constant(name.clone(), value, statement.span)?;
+            }
+            StatementKind::Block(statements) => {
+                return self.execute_in_new_scope(|evaluator| evaluator.execute_statements(statements));
+            }
+            StatementKind::IfStatement(condition, then_branch, else_branch) => {
+                let condition_value = self.evaluate_expression(condition)?;
+                if Self::is_truthy(&condition_value) {
+                    return self.evaluate_statement(then_branch);
+                } else if let Some(else_stmt) = else_branch {
+                    return self.evaluate_statement(else_stmt);
+                }
+            }
+            StatementKind::WhileStatement(condition, body) => {
+                while Self::is_truthy(&self.evaluate_expression(condition)?) {
+                    match self.evaluate_statement(body)? {
+                        ControlFlow::Return(value) => return Ok(ControlFlow::Return(value)),
+                        ControlFlow::Break => break,
+                        ControlFlow::Normal | ControlFlow::Continue => {}
+                    }
+                }
+            }
+            StatementKind::ForStatement(initializer, condition, increment, body) => {
+                // The initializer gets its own scope so `let i` doesn't leak past the loop
+                return self.execute_in_new_scope(|evaluator| {
+                    if !Self::is_nil_placeholder(initializer) {
+                        evaluator.evaluate_statement(initializer)?;
+                    }
+                    while Self::is_truthy(&evaluator.evaluate_expression(condition)?) {
+                        match evaluator.evaluate_statement(body)? {
+                            ControlFlow::Return(value) => return Ok(ControlFlow::Return(value)),
+                            ControlFlow::Break => break,
+                            // `continue` still runs the increment, or the loop would never advance
+                            ControlFlow::Normal | ControlFlow::Continue => {}
+                        }
+                        if !Self::is_nil_placeholder(increment) {
+                            evaluator.evaluate_statement(increment)?;
+                        }
+                    }
+                    Ok(ControlFlow::Normal)
+                });
+            }
+            StatementKind::ForInStatement { variables, iterable, body } => {
+                let value = self.evaluate_expression(iterable)?;
+                let mut cursor = ForInCursor::new(value).inspect_err(|_| self.error_span = Some(iterable.span))?;
+                let binds_keys = cursor.binds_keys();
+
+                while let Some((key, item)) = cursor.next_pair() {
+                    // Each pass gets a fresh scope, so closures made in the body keep the item they saw.
+                    let flow = self.execute_in_new_scope(|evaluator| {
+                        let mut environment = evaluator.environment.borrow_mut();
+                        match variables.as_slice() {
+                            [name] => environment.define(name.clone(), if binds_keys { key } else { item })?,
+                            [first, second] => {
+                                environment.define(first.clone(), key)?;
+                                environment.define(second.clone(), item)?;
+                            }
+                            _ => unreachable!("the parser allows one or two loop variables"),
+                        }
+                        drop(environment);
+                        evaluator.evaluate_statement(body)
+                    })?;
+                    match flow {
+                        ControlFlow::Return(value) => return Ok(ControlFlow::Return(value)),
+                        ControlFlow::Break => break,
+                        ControlFlow::Normal | ControlFlow::Continue => {}
                     }
                 }
+            }
+            StatementKind::FunctionDeclaration { name, params, body } => {
+                let function = FddlFunction {
+                    name: name.clone(),
+                    params: params.clone(),
+                    body: body.clone(),
+                    closure: Rc::clone(&self.environment),
+                    file: self.current_file.clone(),
+                };
+                self.environment.borrow_mut().define(name.clone(), FddlValue::Function(Rc::new(function)))?;
+            }
+            StatementKind::ReturnStatement(value_expr) => {
+                let value = match value_expr {
+                    Some(expr) => self.evaluate_expression(expr)?,
+                    None => FddlValue::Nil,
+                };
+                return Ok(ControlFlow::Return(value));
+            }
+            StatementKind::BreakStatement => return Ok(ControlFlow::Break),
+            StatementKind::ContinueStatement => return Ok(ControlFlow::Continue),
+        }
+        Ok(ControlFlow::Normal)
+    }
+
+    // Executes statements in order, stopping early on `return`, `break` or `continue`.
+    fn execute_statements(&mut self, statements: &[Statement]) -> Result<ControlFlow, RuntimeError> {
+        for statement in statements {
+            let flow = self.evaluate_statement(statement)?;
+            if !matches!(flow, ControlFlow::Normal) {
+                return Ok(flow);
+            }
+        }
+        Ok(ControlFlow::Normal)
+    }
+
+    // Runs `body` inside a fresh scope enclosing the current one.
+    fn execute_in_new_scope<T, F>(&mut self, body: F) -> Result<T, RuntimeError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, RuntimeError>,
+    {
+        let scope = Environment::new_enclosed(Rc::clone(&self.environment)).into_shared();
+        self.execute_in_environment(scope, body)
+    }
+
+    // Runs `body` with `environment` as the current scope, restoring the
+    // previous scope afterwards even if `body` errors.
+    fn execute_in_environment<T, F>(&mut self, environment: SharedEnvironment, body: F) -> Result<T, RuntimeError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, RuntimeError>,
+    {
+        let previous_environment = std::mem::replace(&mut self.environment, environment);
+        let result = body(self);
+        self.environment = previous_environment;
+        result
+    }
+
+    // The parser fills empty for-loop clauses with `nil;` so there is always a statement there.
+    fn is_nil_placeholder(statement: &Statement) -> bool {
+        matches!(
+            &statement.kind,
+            StatementKind::ExpressionStatement(Expression { kind: ExpressionKind::Literal(Literal::Nil), .. })
+        )
+    }
+
+    // Applies a binary operator other than `and`/`or` to already-evaluated operands.
+    // Compound assignments like `+=` go through here too.
+    fn apply_binary(&self, op: &Operator, left_val: FddlValue, right_val: FddlValue) -> Result<FddlValue, RuntimeError> {
+        match op {
+            // Arithmetic
+            Operator::Plus => Self::add(left_val, right_val),
+            Operator::Minus => Self::arithmetic(op, "-", &left_val, &right_val),
+            Operator::Multiply => match (&left_val, &right_val) {
+                (FddlValue::String(text), count) | (count, FddlValue::String(text)) if Number::from_value(count).is_some() => {
+                    Self::repeat(text, Number::from_value(count).unwrap())
+                }
+                _ if Number::from_value(&left_val).is_some() && Number::from_value(&right_val).is_some() => {
+                    Self::arithmetic(op, "*", &left_val, &right_val)
+                }
+                _ => Err(RuntimeError::TypeMismatch(format!("Operands for '*' must be numbers, or a string and a number. Got {} and {}", left_val.type_name(), right_val.type_name()))),
+            },
+            Operator::Divide => Self::arithmetic(op, "/", &left_val, &right_val),
+            Operator::Modulus => Self::arithmetic(op, "%", &left_val, &right_val),
+            Operator::Power => Self::arithmetic(op, "^", &left_val, &right_val),
+            Operator::IntegerDivide => Self::arithmetic(op, "div", &left_val, &right_val),
+
+            // Bitwise
+            Operator::BitAnd
+            | Operator::BitOr
+            | Operator::BitXor
+            | Operator::ShiftLeft
+            | Operator::ShiftRight => Self::bitwise(op, &left_val, &right_val),
+
+            // Comparison
+            Operator::Greater => Self::compare(">", &left_val, &right_val, std::cmp::Ordering::is_gt),
+            Operator::GreaterEqual => Self::compare(">=", &left_val, &right_val, std::cmp::Ordering::is_ge),
+            Operator::Less => Self::compare("<", &left_val, &right_val, std::cmp::Ordering::is_lt),
+            Operator::LessEqual => Self::compare("<=", &left_val, &right_val, std::cmp::Ordering::is_le),
 
-                // Restore the outer environment
-                if let Some(parent_env_box) = self.environment.parent.take() { // .take() to get ownership
-                    self.environment = *parent_env_box; // Move parent back to be the current environment
+            // Equality
+            Operator::EqualEqual => Ok(FddlValue::Boolean(left_val == right_val)),
+            Operator::NotEqual => Ok(FddlValue::Boolean(left_val != right_val)),
+            Operator::AlmostEqual => Ok(FddlValue::Boolean(self.almost_equal(&left_val, &right_val))),
+            
+            // And & Or short-circuit, so evaluate_expression_kind handles them itself.
+            // This _ should catch any other Operator variants not explicitly handled here.
+            _ => Err(RuntimeError::TypeMismatch(format!(
+                "Unsupported binary operator '{:?}' after operand evaluation.", op
+            ))),
+        }
+    }
+
+    // Bitwise operators treat their operands as 64-bit signed integers, so both
+    // must be whole numbers. `>>` keeps the sign: `-8 >> 1` is -4.
+    fn bitwise(op: &Operator, left: &FddlValue, right: &FddlValue) -> Result<FddlValue, RuntimeError> {
+        let symbol = match op {
+            Operator::BitAnd => "&",
+            Operator::BitOr => "|",
+            Operator::BitXor => "~",
+            Operator::ShiftLeft => "<<",
+            _ => ">>",
+        };
+        let (l, r) = (Self::integer_operand(symbol, left)?, Self::integer_operand(symbol, right)?);
+
+        match op {
+            Operator::BitAnd => Ok(FddlValue::Int(l & r)),
+            Operator::BitOr => Ok(FddlValue::Int(l | r)),
+            Operator::BitXor => Ok(FddlValue::Int(l ^ r)),
+            _ => {
+                if !(0..64).contains(&r) {
+                    return Err(RuntimeError::TypeMismatch(format!(
+                        "Shift amount for '{}' must be between 0 and 63. Got {}",
+                        symbol, r
+                    )));
+                }
+                if *op == Operator::ShiftRight {
+                    Ok(FddlValue::Int(l >> r))
                 } else {
-                    // This should ideally not happen if scopes are managed correctly.
-                    eprintln!("Warning: Exited a block scope that had no parent environment. Resetting to global.");
-                    self.environment = Environment::new(); // Fallback
+                    Ok(numeric::shift_left(l, r as u32))
                 }
-                return block_execution_result; // Return the result from the block (e.g., if an error occurred)
             }
-            // TODO: Implement IfStatement, WhileStatement, ForStatement evaluation
-            // TODO: Implement FunctionDeclaration (store function object in environment)
-            // TODO: Implement ReturnStatement (special handling for unwinding and returning value)
-            _ => {
-                println!("Interpreter: Skipping unimplemented statement: {:?}", statement);
+        }
+    }
+
+    // Ints, and floats with no fractional part (`6.0 & 3` is 2). Ints past 64 bits
+    // aren't supported.
+    fn integer_operand(symbol: &str, value: &FddlValue) -> Result<i64, RuntimeError> {
+        if let FddlValue::BigInt(n) = value {
+            return Err(RuntimeError::TypeMismatch(format!(
+                "Operands for '{}' must fit in 64 bits. Got {}",
+                symbol, n
+            )));
+        }
+        Number::from_value(value).and_then(|n| n.to_whole()).ok_or_else(|| {
+            RuntimeError::TypeMismatch(format!("Operands for '{}' must be whole numbers. Got {}", symbol, describe_number(value)))
+        })
+    }
+
+    // `-`, `/`, `%`, `^` and `div`; see numeric.rs for how ints and floats mix.
+    fn arithmetic(op: &Operator, symbol: &str, left: &FddlValue, right: &FddlValue) -> Result<FddlValue, RuntimeError> {
+        match (Number::from_value(left), Number::from_value(right)) {
+            (Some(l), Some(r)) => numeric::arithmetic(op, symbol, l, r),
+            _ => Err(RuntimeError::TypeMismatch(format!(
+                "Operands for '{}' must be numbers. Got {} and {}",
+                symbol,
+                left.type_name(),
+                right.type_name()
+            ))),
+        }
+    }
+
+    // Stores into `target` and returns the stored value. A compound assignment like
+    // `+=` reads the target first and combines it with `value`.
+    fn assign(&mut self, target: &AssignTarget, operator: Option<&Operator>, value: &Expression) -> Result<FddlValue, RuntimeError> {
+        match target {
+            AssignTarget::Variable(name) => {
+                let current = match operator {
+                    Some(_) => Some(self.environment.borrow().get(name)?),
+                    None => None,
+                };
+                let val_to_assign = self.assigned_value(current, operator, value)?;
+                self.environment.borrow_mut().assign(name, val_to_assign.clone())?;
+                Ok(val_to_assign)
+            }
+            AssignTarget::Index { object, index } => {
+                let object = self.evaluate_expression(object)?;
+                let index = self.evaluate_expression(index)?;
+                let current = match operator {
+                    Some(_) => Some(Self::get_index(&object, &index)?),
+                    None => None,
+                };
+                let val_to_assign = self.assigned_value(current, operator, value)?;
+                Self::set_index(&object, &index, val_to_assign.clone())?;
+                Ok(val_to_assign)
+            }
+            AssignTarget::Field { object, name } => {
+                let map = match self.evaluate_expression(object)? {
+                    FddlValue::Map(map) => map,
+                    FddlValue::Module(module) => {
+                        return Err(RuntimeError::TypeMismatch(format!(
+                            "Can't assign to '{}.{}' from outside module '{}'.",
+                            module.name, name, module.name
+                        )))
+                    }
+                    other => {
+                        return Err(RuntimeError::TypeMismatch(format!(
+                            "Only map fields can be assigned with '.'. Got {}",
+                            other.type_name()
+                        )))
+                    }
+                };
+                let key = MapKey::String(name.clone());
+                let current = operator.map(|_| map.borrow().get(&key).cloned().unwrap_or(FddlValue::Nil));
+                let val_to_assign = self.assigned_value(current, operator, value)?;
+                map.borrow_mut().insert(key, val_to_assign.clone());
+                Ok(val_to_assign)
             }
         }
-        Ok(())
+    }
+
+    // The value an assignment stores: `value` itself, or `current <operator> value`.
+    fn assigned_value(
+        &mut self,
+        current: Option<FddlValue>,
+        operator: Option<&Operator>,
+        value: &Expression,
+    ) -> Result<FddlValue, RuntimeError> {
+        let value = self.evaluate_expression(value)?;
+        match (operator, current) {
+            (Some(op), Some(current)) => self.apply_binary(op, current, value),
+            _ => Ok(value),
+        }
     }
 
     fn evaluate_expression(&mut self, expression: &Expression) -> Result<FddlValue, RuntimeError> {
-        match expression {
-            Expression::Literal(literal) => {
+        let result = self.evaluate_expression_kind(expression);
+        if result.is_err() && self.error_span.is_none() {
+            self.error_span = Some(expression.span);
+        }
+        result
+    }
+
+    fn evaluate_expression_kind(&mut self, expression: &Expression) -> Result<FddlValue, RuntimeError> {
+        match &expression.kind {
+            ExpressionKind::Literal(literal) => {
                 match literal {
+                    Literal::Int(n) => Ok(FddlValue::Int(*n)),
+                    Literal::BigInt(digits) => Ok(Number::from_big(
+                        BigInt::parse(digits).expect("the lexer only produces valid digits"),
+                    )
+                    .to_value()),
                     Literal::Number(n) => Ok(FddlValue::Number(*n)),
                     Literal::Boolean(b) => Ok(FddlValue::Boolean(*b)),
                     Literal::String(s) => Ok(FddlValue::String(s.clone())),
+                    Literal::Symbol(name) => Ok(FddlValue::Symbol(Symbol::intern(name))),
                     Literal::Nil => Ok(FddlValue::Nil),
                 }
             },
 
-            Expression::Variable(name) => {
-                self.environment.get(name)
+            ExpressionKind::Variable(name) => {
+                self.environment.borrow().get(name)
             },
 
-            Expression::Unary(op, right_expr) => {
+            ExpressionKind::Unary(op, right_expr) => {
                 let right_val = self.evaluate_expression(right_expr)?;
                 match op {
-                    Operator::Minus => {
-                        if let FddlValue::Number(n) = right_val {
-                            Ok(FddlValue::Number(-n))
-                        } else {
-                            Err(RuntimeError::TypeMismatch(
-                                "Operand for unary '-' must be a number.".to_string(),
-                            ))
-                        }
-                    }
+                    Operator::Minus => match Number::from_value(&right_val) {
+                        Some(n) => Ok(numeric::negate(n)),
+                        None => Err(RuntimeError::TypeMismatch(
+                            "Operand for unary '-' must be a number.".to_string(),
+                        )),
+                    },
                     Operator::Not => {
                         Ok(FddlValue::Boolean(!Self::is_truthy(&right_val)))
                     }
@@ -222,18 +1070,10 @@ impl Evaluator {
                     }
                     Operator::Almost => { // '~' operator
                         match right_val {
-                            FddlValue::Number(n) => {
-                                if n == 0.0 {
-                                    Ok(FddlValue::Number(0.1337)) // Arbitrary small chaotic number
-                                } else {
-                                    let bits = n.to_bits();
-                                    let offset_seed = (bits >> 16) & 0xFFF;
-                                    let scale_seed = bits & 0xFFF;
-                                    let chaotic_offset = (offset_seed as f64 / 4095.0 - 0.5) * n.abs() * 0.2;
-                                    let chaotic_scale = 1.0 + (scale_seed as f64 / 4095.0 - 0.5) * 0.1;
-                                    Ok(FddlValue::Number((n + chaotic_offset) * chaotic_scale))
-                                }
-                            }
+                            FddlValue::Number(n) => Ok(FddlValue::Number(self.almost.apply(n))),
+                            // Nudging a whole number off by a little leaves a float
+                            FddlValue::Int(n) => Ok(FddlValue::Number(self.almost.apply(n as f64))),
+                            FddlValue::BigInt(n) => Ok(FddlValue::Number(self.almost.apply(n.to_f64()))),
                             FddlValue::Boolean(b) => Ok(FddlValue::Boolean(!b)),
                             FddlValue::String(s) => {
                                 if s.is_empty() {
@@ -245,6 +1085,24 @@ impl Evaluator {
                                 }
                             }
                             FddlValue::Nil => Ok(FddlValue::String("almost nil?".to_string())),
+                            FddlValue::Function(_) | FddlValue::Builtin(_) => Err(RuntimeError::TypeMismatch(
+                                "Operand for unary '~' cannot be a function.".to_string(),
+                            )),
+                            FddlValue::Module(_) => Err(RuntimeError::TypeMismatch(
+                                "Operand for unary '~' cannot be a module.".to_string(),
+                            )),
+                            FddlValue::Symbol(_) => Err(RuntimeError::TypeMismatch(
+                                "Operand for unary '~' cannot be a symbol.".to_string(),
+                            )),
+                            FddlValue::List(_) => Err(RuntimeError::TypeMismatch(
+                                "Operand for unary '~' cannot be a list.".to_string(),
+                            )),
+                            FddlValue::Map(_) => Err(RuntimeError::TypeMismatch(
+                                "Operand for unary '~' cannot be a map.".to_string(),
+                            )),
+                            FddlValue::Range(_) => Err(RuntimeError::TypeMismatch(
+                                "Operand for unary '~' cannot be a range.".to_string(),
+                            )),
                         }
                     }
                     // Add other unary operators if you have them in your Operator enum
@@ -255,7 +1113,7 @@ impl Evaluator {
                 }
             },
 
-            Expression::Binary(left_expr, op, right_expr) => {
+            ExpressionKind::Binary(left_expr, op, right_expr) => {
                 // Handle logical AND and OR first for short-circuiting
                 match op {
                     Operator::And => {
@@ -264,7 +1122,7 @@ impl Evaluator {
                             return Ok(FddlValue::Boolean(false)); // Short-circuit
                         }
                         let right_val = self.evaluate_expression(right_expr)?;
-                        return Ok(FddlValue::Boolean(Self::is_truthy(&right_val)));
+                        Ok(FddlValue::Boolean(Self::is_truthy(&right_val)))
                     }
                     Operator::Or => {
                         let left_val = self.evaluate_expression(left_expr)?;
@@ -272,91 +1130,316 @@ impl Evaluator {
                             return Ok(FddlValue::Boolean(true)); // Short-circuit
                         }
                         let right_val = self.evaluate_expression(right_expr)?;
-                        return Ok(FddlValue::Boolean(Self::is_truthy(&right_val)));
+                        Ok(FddlValue::Boolean(Self::is_truthy(&right_val)))
                     }
                     _ => { // For all other binary operators, evaluate both operands first
                         let left_val = self.evaluate_expression(left_expr)?;
                         let right_val = self.evaluate_expression(right_expr)?;
+                        self.apply_binary(op, left_val, right_val)
+                    }
+                }
+            },
 
-                        match op {
-                            // Arithmetic
-                            Operator::Plus => {
-                                if let (FddlValue::Number(l), FddlValue::Number(r)) = (&left_val, &right_val) {
-                                    Ok(FddlValue::Number(l + r))
-                                } else { // TODO: String concatenation?
-                                    Err(RuntimeError::TypeMismatch(format!("Operands for '+' must be numbers. Got {:?} and {:?}", left_val, right_val)))
-                                }
-                            }
-                            Operator::Minus => {
-                                if let (FddlValue::Number(l), FddlValue::Number(r)) = (&left_val, &right_val) {
-                                    Ok(FddlValue::Number(l - r))
-                                } else {
-                                    Err(RuntimeError::TypeMismatch(format!("Operands for '-' must be numbers. Got {:?} and {:?}", left_val, right_val)))
-                                }
-                            }
-                            Operator::Multiply => {
-                                if let (FddlValue::Number(l), FddlValue::Number(r)) = (&left_val, &right_val) {
-                                    Ok(FddlValue::Number(l * r))
-                                } else {
-                                    Err(RuntimeError::TypeMismatch(format!("Operands for '*' must be numbers. Got {:?} and {:?}", left_val, right_val)))
-                                }
-                            }
-                            Operator::Divide => {
-                                if let (FddlValue::Number(l), FddlValue::Number(r)) = (&left_val, &right_val) {
-                                    if *r == 0.0 { Err(RuntimeError::DivisionByZero) } else { Ok(FddlValue::Number(l / r)) }
-                                } else {
-                                    Err(RuntimeError::TypeMismatch(format!("Operands for '/' must be numbers. Got {:?} and {:?}", left_val, right_val)))
-                                }
-                            }
-                            Operator::Modulus => {
-                                if let (FddlValue::Number(l), FddlValue::Number(r)) = (&left_val, &right_val) {
-                                    if *r == 0.0 { Err(RuntimeError::TypeMismatch("Modulus by zero.".to_string())) } else { Ok(FddlValue::Number(l % r)) }
-                                } else {
-                                    Err(RuntimeError::TypeMismatch(format!("Operands for '%' must be numbers. Got {:?} and {:?}", left_val, right_val)))
-                                }
-                            }
+            ExpressionKind::Function { params, body } => {
+                let function = FddlFunction {
+                    name: "anonymous".to_string(), // Shows up as `<func anonymous>`
+                    params: params.clone(),
+                    body: body.clone(),
+                    closure: Rc::clone(&self.environment),
+                    file: self.current_file.clone(),
+                };
+                Ok(FddlValue::Function(Rc::new(function)))
+            },
 
-                            // Comparison
-                            Operator::Greater => {
-                                if let (FddlValue::Number(l), FddlValue::Number(r)) = (&left_val, &right_val) { Ok(FddlValue::Boolean(l > r)) } else { Err(RuntimeError::TypeMismatch("Operands for '>' must be numbers.".to_string())) }
-                            }
-                            Operator::GreaterEqual => {
-                                if let (FddlValue::Number(l), FddlValue::Number(r)) = (&left_val, &right_val) { Ok(FddlValue::Boolean(l >= r)) } else { Err(RuntimeError::TypeMismatch("Operands for '>=' must be numbers.".to_string())) }
-                            }
-                            Operator::Less => {
-                                if let (FddlValue::Number(l), FddlValue::Number(r)) = (&left_val, &right_val) { Ok(FddlValue::Boolean(l < r)) } else { Err(RuntimeError::TypeMismatch("Operands for '<' must be numbers.".to_string())) }
-                            }
-                            Operator::LessEqual => {
-                                if let (FddlValue::Number(l), FddlValue::Number(r)) = (&left_val, &right_val) { Ok(FddlValue::Boolean(l <= r)) } else { Err(RuntimeError::TypeMismatch("Operands for '<=' must be numbers.".to_string())) }
-                            }
+            ExpressionKind::Assignment { target, operator, value } => {
+                self.assign(target, operator.as_ref(), value)
+            },
 
-                            // Equality
-                            Operator::EqualEqual => Ok(FddlValue::Boolean(left_val == right_val)),
-                            Operator::NotEqual => Ok(FddlValue::Boolean(left_val != right_val)),
-                            
-                            // And & Or are handled above due to short-circuiting.
-                            // This _ should catch any other Operator variants not explicitly handled here.
-                            _ => Err(RuntimeError::TypeMismatch(format!(
-                                "Unsupported binary operator '{:?}' after operand evaluation.", op
-                            ))),
+            ExpressionKind::Grouping(inner_expr) => {
+                self.evaluate_expression(inner_expr)
+            },
+
+            ExpressionKind::FunctionCall(callee_expr, argument_exprs) => {
+                let callee = self.evaluate_expression(callee_expr)?;
+
+                let mut arguments = Vec::with_capacity(argument_exprs.len());
+                for argument_expr in argument_exprs {
+                    arguments.push(self.evaluate_expression(argument_expr)?);
+                }
+
+                match callee {
+                    FddlValue::Function(function) => {
+                        let result = self.call_function(&function, arguments);
+                        // A span inside another file means nothing in this one, so blame the call instead.
+                        if result.is_err() && function.file != self.current_file {
+                            self.error_span = None;
+                        }
+                        result
+                    }
+                    FddlValue::Builtin(builtin) => {
+                        if arguments.len() != builtin.arity() {
+                            return Err(RuntimeError::IncorrectArgumentCount {
+                                function_name: builtin.name().to_string(),
+                                expected: builtin.arity(),
+                                found: arguments.len(),
+                            });
                         }
+                        builtin.call(arguments)
                     }
+                    other => Err(RuntimeError::TypeMismatch(format!(
+                        "Can only call functions. Got {}",
+                        other.type_name()
+                    ))),
                 }
             },
 
-            Expression::Grouping(inner_expr) => {
-                self.evaluate_expression(inner_expr)
+            ExpressionKind::Get(object_expr, member) => {
+                match self.evaluate_expression(object_expr)? {
+                    FddlValue::Module(module) => match module.environment.borrow().get_member(member) {
+                        Some((value, true)) => Ok(value),
+                        Some((_, false)) => Err(RuntimeError::PrivateMember {
+                            module: module.name.clone(),
+                            member: member.clone(),
+                        }),
+                        None => Err(RuntimeError::UndefinedMember {
+                            module: module.name.clone(),
+                            member: member.clone(),
+                        }),
+                    },
+                    // A missing field is nil, like a missing key
+                    FddlValue::Map(map) => Ok(map
+                        .borrow()
+                        .get(&MapKey::String(member.clone()))
+                        .cloned()
+                        .unwrap_or(FddlValue::Nil)),
+                    other => Err(RuntimeError::TypeMismatch(format!(
+                        "Only modules and maps have members. Got {}",
+                        other.type_name()
+                    ))),
+                }
+            },
+
+            ExpressionKind::List(element_exprs) => {
+                let mut items = Vec::with_capacity(element_exprs.len());
+                for element_expr in element_exprs {
+                    items.push(self.evaluate_expression(element_expr)?);
+                }
+                Ok(FddlValue::List(Rc::new(RefCell::new(items))))
+            },
+
+            ExpressionKind::Index(object_expr, index_expr) => {
+                let object = self.evaluate_expression(object_expr)?;
+                let index = self.evaluate_expression(index_expr)?;
+                Self::get_index(&object, &index)
+            },
+
+            ExpressionKind::Range { start, end, inclusive } => {
+                let start = self.evaluate_expression(start)?;
+                let end = self.evaluate_expression(end)?;
+                match (Number::from_value(&start), Number::from_value(&end)) {
+                    (Some(start), Some(end)) => Ok(FddlValue::Range(FddlRange { start, end, inclusive: *inclusive })),
+                    _ => Err(RuntimeError::TypeMismatch(format!(
+                        "Range bounds must be numbers. Got {} and {}",
+                        start.type_name(),
+                        end.type_name()
+                    ))),
+                }
             },
 
-            // TODO: Implement Expression::FunctionCall evaluation
+            ExpressionKind::Map(entry_exprs) => {
+                let mut map = FddlMap::new();
+                for (key_expr, value_expr) in entry_exprs {
+                    let key = Self::map_key(&self.evaluate_expression(key_expr)?)?;
+                    let value = self.evaluate_expression(value_expr)?;
+                    map.insert(key, value);
+                }
+                Ok(FddlValue::Map(Rc::new(RefCell::new(map))))
+            },
 
-            _ => { // Fallback for unimplemented expression types
-                println!("Interpreter: Unimplemented expression: {:?}", expression);
-                Err(RuntimeError::TypeMismatch(format!(
-                    "Unimplemented expression type: {:?}",
-                    expression
+            ExpressionKind::Interpolation(parts) => {
+                let mut text = String::new();
+                for part in parts {
+                    match part {
+                        InterpolationPart::Literal(literal) => text.push_str(literal),
+                        InterpolationPart::Expression(expr) => {
+                            text.push_str(&self.evaluate_expression(expr)?.to_string())
+                        }
+                    }
+                }
+                Ok(FddlValue::String(text))
+            },
+        }
+    }
+
+    fn call_function(&mut self, function: &FddlFunction, arguments: Vec<FddlValue>) -> Result<FddlValue, RuntimeError> {
+        if arguments.len() != function.params.len() {
+            return Err(RuntimeError::IncorrectArgumentCount {
+                function_name: function.name.clone(),
+                expected: function.params.len(),
+                found: arguments.len(),
+            });
+        }
+
+        // Runaway recursion would otherwise overflow the Rust stack and abort.
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::StackOverflow(function.name.clone()));
+        }
+
+        // Parameters live in a fresh scope enclosing the one the function was declared in.
+        let mut call_environment = Environment::new_enclosed(Rc::clone(&function.closure));
+        for (param, argument) in function.params.iter().zip(arguments) {
+            call_environment.define(param.clone(), argument)?;
+        }
+
+        self.call_depth += 1;
+        let result = self.execute_in_environment(call_environment.into_shared(), |evaluator| {
+            match evaluator.execute_statements(&function.body)? {
+                ControlFlow::Return(value) => Ok(value),
+                // The parser keeps `break` and `continue` from escaping a function body.
+                ControlFlow::Normal | ControlFlow::Break | ControlFlow::Continue => Ok(FddlValue::Nil),
+            }
+        });
+        self.call_depth -= 1;
+        result
+    }
+}
+
+// --- Lists and maps ---
+impl Evaluator {
+    // `object[index]`. Reading a key that isn't in a map gives nil.
+    fn get_index(object: &FddlValue, index: &FddlValue) -> Result<FddlValue, RuntimeError> {
+        match object {
+            FddlValue::List(list) => {
+                let items = list.borrow();
+                let position = Self::list_position(index, items.len())?;
+                Ok(items[position].clone())
+            }
+            FddlValue::Map(map) => Ok(map.borrow().get(&Self::map_key(index)?).cloned().unwrap_or(FddlValue::Nil)),
+            other => Err(RuntimeError::TypeMismatch(format!(
+                "Only lists and maps can be indexed. Got {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    // `object[index] = value`. Lists can only replace existing items; maps add the key if needed.
+    fn set_index(object: &FddlValue, index: &FddlValue, value: FddlValue) -> Result<(), RuntimeError> {
+        match object {
+            FddlValue::List(list) => {
+                let mut items = list.borrow_mut();
+                let position = Self::list_position(index, items.len())?;
+                items[position] = value;
+                Ok(())
+            }
+            FddlValue::Map(map) => {
+                map.borrow_mut().insert(Self::map_key(index)?, value);
+                Ok(())
+            }
+            other => Err(RuntimeError::TypeMismatch(format!(
+                "Only lists and maps can be indexed. Got {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    fn map_key(value: &FddlValue) -> Result<MapKey, RuntimeError> {
+        MapKey::from_value(value).ok_or_else(|| {
+            RuntimeError::TypeMismatch(format!(
+                "Map keys must be strings, numbers, booleans, symbols or nil. Got {}",
+                value.type_name()
+            ))
+        })
+    }
+
+    // Turns an fddl index into a position in a list of `length` items. Negative
+    // indices count back from the end, so -1 is the last item.
+    fn list_position(index: &FddlValue, length: usize) -> Result<usize, RuntimeError> {
+        let index = match Number::from_value(index).and_then(|n| n.to_whole()) {
+            Some(index) => index,
+            None => {
+                return Err(RuntimeError::TypeMismatch(format!(
+                    "List indices must be whole numbers. Got {}",
+                    describe_number(index)
                 )))
             }
+        };
+
+        // i128 so `length + index` can't overflow for any i64 index
+        let position = if index < 0 { length as i128 + index as i128 } else { index as i128 };
+        if position < 0 || position >= length as i128 {
+            return Err(RuntimeError::IndexOutOfBounds { index, length });
+        }
+        Ok(position as usize)
+    }
+}
+
+// --- Imports ---
+impl Evaluator {
+    // Loads the file behind `import path`, running it the first time it is imported
+    // and handing out the cached module after that.
+    fn import_module(&mut self, path: &str, name: &str) -> Result<Rc<FddlModule>, RuntimeError> {
+        let resolved = self.resolve_import_path(path);
+        let import_failed = |message: String| RuntimeError::ImportFailed {
+            path: resolved.display().to_string(),
+            message,
+        };
+
+        let file = std::fs::canonicalize(&resolved).map_err(|e| import_failed(e.to_string()))?;
+        if let Some(module) = self.modules.get(&file) {
+            return Ok(Rc::clone(module));
+        }
+        if let Some(position) = self.loading.iter().position(|loading| *loading == file) {
+            let cycle = self.loading[position..]
+                .iter()
+                .chain(std::iter::once(&file))
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(RuntimeError::ImportCycle(cycle));
+        }
+
+        let source = std::fs::read_to_string(&file).map_err(|e| import_failed(e.to_string()))?;
+        let mut parser = Parser::new(Lexer::new(source).scan_spanned_tokens());
+        let program = parser
+            .parse_program()
+            .and_then(|program| resolve(&program).map(|()| program))
+            .map_err(|errors| import_failed(errors[0].to_string()))?;
+
+        // An imported file gets its own global scope, so it can't see the importer's variables.
+        let module_environment = Environment::global().into_shared();
+        self.loading.push(file.clone());
+        let importer = self.current_file.replace(file.clone());
+        let result = self.execute_in_environment(Rc::clone(&module_environment), |evaluator| {
+            evaluator.execute_statements(&program)
+        });
+        self.current_file = importer;
+        self.loading.pop();
+
+        if let Err(error) = result {
+            // The error's location is in the other file, so blame the import statement instead.
+            let location = self.error_span.take();
+            return Err(match (error, location) {
+                (error @ RuntimeError::ImportCycle(_), _) => error,
+                (error, Some(span)) => import_failed(format!("[line {}, column {}] {}", span.line, span.column, error)),
+                (error, None) => import_failed(error.to_string()),
+            });
+        }
+
+        let module = Rc::new(FddlModule { name: name.to_string(), environment: module_environment });
+        self.modules.insert(file, Rc::clone(&module));
+        Ok(module)
+    }
+
+    // Imports are relative to the importing file; `.fddl` is added if the path has no extension.
+    fn resolve_import_path(&self, path: &str) -> PathBuf {
+        let base = self
+            .current_file
+            .as_deref()
+            .and_then(Path::parent)
+            .unwrap_or_else(|| Path::new(""));
+        let mut resolved = base.join(path);
+        if resolved.extension().is_none() {
+            resolved.set_extension("fddl");
         }
+        resolved
     }
 }
@@ -0,0 +1,90 @@
+use crate::interpreter::evaluator::{FddlValue, RuntimeError, SharedList, SharedMap};
+use crate::interpreter::numeric::{self, Number};
+use crate::parser::ast::Operator;
+
+// `start..end` or `start..=end`. Ranges only store their bounds; the numbers in
+// between are produced one at a time as a loop asks for them. They step by 1 and
+// are ints when `start` is an int, floats otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FddlRange {
+    pub start: Number,
+    pub end: Number,
+    pub inclusive: bool,
+}
+
+impl FddlRange {
+    // The `index`th number in the range (counting from 0), if there is one.
+    fn nth(&self, index: usize) -> Option<Number> {
+        let offset = Number::Int(i64::try_from(index).ok()?);
+        let value = numeric::arithmetic(&Operator::Plus, "+", self.start.clone(), offset).ok()?;
+        let value = Number::from_value(&value)?;
+        let ordering = numeric::compare(&value, &self.end)?;
+        let in_range = if self.inclusive { ordering.is_le() } else { ordering.is_lt() };
+        in_range.then_some(value)
+    }
+}
+
+impl std::fmt::Display for FddlRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operator = if self.inclusive { "..=" } else { ".." };
+        write!(f, "{}{}{}", self.start.to_value(), operator, self.end.to_value())
+    }
+}
+
+// Walks the value a `for (x in ...)` loop iterates over. Each step gives a pair:
+// the key and value for maps, or the position and item for everything else.
+//
+// Lists and maps are read one step at a time rather than copied up front, so
+// the loop sees items that the body adds or changes.
+pub enum ForInCursor {
+    List(SharedList, usize),
+    Map(SharedMap, usize),
+    Chars(Vec<char>, usize),
+    Range(FddlRange, usize),
+}
+
+impl ForInCursor {
+    pub fn new(iterable: FddlValue) -> Result<Self, RuntimeError> {
+        match iterable {
+            FddlValue::List(list) => Ok(ForInCursor::List(list, 0)),
+            FddlValue::Map(map) => Ok(ForInCursor::Map(map, 0)),
+            FddlValue::String(s) => Ok(ForInCursor::Chars(s.chars().collect(), 0)),
+            FddlValue::Range(range) => Ok(ForInCursor::Range(range, 0)),
+            other => Err(RuntimeError::TypeMismatch(format!(
+                "Can only loop over lists, maps, strings and ranges. Got {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    // Whether a single loop variable gets the first half of each pair (map keys)
+    // rather than the second (items).
+    pub fn binds_keys(&self) -> bool {
+        matches!(self, ForInCursor::Map(..))
+    }
+
+    pub fn next_pair(&mut self) -> Option<(FddlValue, FddlValue)> {
+        match self {
+            ForInCursor::List(list, position) => {
+                let item = list.borrow().get(*position).cloned()?;
+                *position += 1;
+                Some((FddlValue::Int(*position as i64 - 1), item))
+            }
+            ForInCursor::Map(map, position) => {
+                let (key, value) = map.borrow().entry_at(*position)?;
+                *position += 1;
+                Some((key.to_value(), value))
+            }
+            ForInCursor::Chars(chars, position) => {
+                let c = *chars.get(*position)?;
+                *position += 1;
+                Some((FddlValue::Int(*position as i64 - 1), FddlValue::String(c.to_string())))
+            }
+            ForInCursor::Range(range, position) => {
+                let value = range.nth(*position)?;
+                *position += 1;
+                Some((FddlValue::Int(*position as i64 - 1), value.to_value()))
+            }
+        }
+    }
+}
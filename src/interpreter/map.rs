@@ -0,0 +1,95 @@
+use crate::interpreter::bigint::BigInt;
+use crate::interpreter::evaluator::FddlValue;
+use crate::interpreter::numeric::Number;
+use crate::interpreter::symbol::Symbol;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// The values that can be used as map keys. Lists, maps, functions and modules
+// can't: they are mutable or have no sensible notion of equality.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    String(String),
+    Int(i64),
+    BigInt(BigInt),
+    Number(u64), // Bits of a float that isn't whole
+    Boolean(bool),
+    Symbol(Symbol),
+    Nil,
+}
+
+impl MapKey {
+    pub fn from_value(value: &FddlValue) -> Option<MapKey> {
+        match value {
+            FddlValue::String(s) => Some(MapKey::String(s.clone())),
+            FddlValue::Int(n) => Some(MapKey::Int(*n)),
+            FddlValue::BigInt(n) => Some(MapKey::BigInt((**n).clone())),
+            FddlValue::Number(n) if n.is_nan() => None, // NaN would never find itself again
+            // 1.0 == 1, so both have to find the same entry (and -0.0 the same one as 0)
+            FddlValue::Number(n) if n.fract() == 0.0 => MapKey::from_value(&Number::from_big(BigInt::from_f64(*n)?).to_value()),
+            FddlValue::Number(n) => Some(MapKey::Number(n.to_bits())),
+            FddlValue::Boolean(b) => Some(MapKey::Boolean(*b)),
+            FddlValue::Symbol(symbol) => Some(MapKey::Symbol(*symbol)),
+            FddlValue::Nil => Some(MapKey::Nil),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> FddlValue {
+        match self {
+            MapKey::String(s) => FddlValue::String(s.clone()),
+            MapKey::Int(n) => FddlValue::Int(*n),
+            MapKey::BigInt(n) => FddlValue::BigInt(Rc::new(n.clone())),
+            MapKey::Number(bits) => FddlValue::Number(f64::from_bits(*bits)),
+            MapKey::Boolean(b) => FddlValue::Boolean(*b),
+            MapKey::Symbol(symbol) => FddlValue::Symbol(*symbol),
+            MapKey::Nil => FddlValue::Nil,
+        }
+    }
+}
+
+// A map that remembers insertion order: iterating and printing go through entries
+// in the order their keys were first added. Updating a key keeps its place.
+#[derive(Debug, Clone, Default)]
+pub struct FddlMap {
+    entries: Vec<(MapKey, FddlValue)>,
+    positions: HashMap<MapKey, usize>,
+}
+
+impl FddlMap {
+    pub fn new() -> Self {
+        FddlMap::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&FddlValue> {
+        self.positions.get(key).map(|&position| &self.entries[position].1)
+    }
+
+    pub fn insert(&mut self, key: MapKey, value: FddlValue) {
+        match self.positions.get(&key) {
+            Some(&position) => self.entries[position].1 = value,
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    // The entry at `position` in insertion order.
+    pub fn entry_at(&self, position: usize) -> Option<(MapKey, FddlValue)> {
+        self.entries.get(position).cloned()
+    }
+
+    // Entries in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&MapKey, &FddlValue)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+}
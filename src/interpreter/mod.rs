@@ -1 +1,8 @@
-pub mod evaluator;
\ No newline at end of file
+pub mod almost;
+pub mod bigint;
+pub mod builtins;
+pub mod evaluator;
+pub mod iteration;
+pub mod map;
+pub mod numeric;
+pub mod symbol;
\ No newline at end of file
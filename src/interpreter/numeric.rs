@@ -0,0 +1,275 @@
+use crate::interpreter::bigint::BigInt;
+use crate::interpreter::evaluator::{FddlValue, RuntimeError};
+use crate::parser::ast::Operator;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+// fddl has two kinds of number: integers and floats. The rules for mixing them:
+// - int with int gives an int, except `/`, which always gives a float (`10 / 3` is
+//   3.333...), and `^` with a negative exponent. Use `div` for whole-number division.
+// - anything with a float gives a float.
+// - ints are 64-bit until a result doesn't fit, then they grow as needed
+//   (`2 ^ 100` is exact). Results that fit in 64 bits again shrink back.
+// - ints and floats compare by value, so `1 == 1.0` and `2 < 2.5`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Big(Rc<BigInt>), // Always outside the i64 range
+    Float(f64),
+}
+
+// The most bits `^` and `<<` will build a number out to (a few hundred thousand
+// digits), so a typo like `10 ^ 10 ^ 10` fails instead of eating all the memory.
+const MAX_BITS: u64 = 1 << 20;
+
+impl Number {
+    pub fn from_value(value: &FddlValue) -> Option<Number> {
+        match value {
+            FddlValue::Int(n) => Some(Number::Int(*n)),
+            FddlValue::BigInt(n) => Some(Number::Big(n.clone())),
+            FddlValue::Number(n) => Some(Number::Float(*n)),
+            _ => None,
+        }
+    }
+
+    // An int of any size, as an i64 when it fits.
+    pub fn from_big(n: BigInt) -> Number {
+        match n.to_i64() {
+            Some(n) => Number::Int(n),
+            None => Number::Big(Rc::new(n)),
+        }
+    }
+
+    pub fn to_value(&self) -> FddlValue {
+        match self {
+            Number::Int(n) => FddlValue::Int(*n),
+            Number::Big(n) => FddlValue::BigInt(n.clone()),
+            Number::Float(n) => FddlValue::Number(*n),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Int(n) => *n as f64,
+            Number::Big(n) => n.to_f64(),
+            Number::Float(n) => *n,
+        }
+    }
+
+    // The number as an i64 if it is whole and in range: small ints always, floats like 3.0.
+    pub fn to_whole(&self) -> Option<i64> {
+        match self {
+            Number::Int(n) => Some(*n),
+            // fract() is NaN for infinities, so they fail the first check
+            Number::Float(n) if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n < i64::MAX as f64 => Some(*n as i64),
+            Number::Big(_) | Number::Float(_) => None,
+        }
+    }
+
+    // Ints of either size; None for floats.
+    fn to_big(&self) -> Option<BigInt> {
+        match self {
+            Number::Int(n) => Some(BigInt::from_i64(*n)),
+            Number::Big(n) => Some((**n).clone()),
+            Number::Float(_) => None,
+        }
+    }
+}
+
+// `+`, `-`, `*`, `/`, `%`, `div` and `^` on two numbers.
+pub fn arithmetic(op: &Operator, symbol: &str, left: Number, right: Number) -> Result<FddlValue, RuntimeError> {
+    if let (Number::Int(l), Number::Int(r)) = (&left, &right) {
+        return int_arithmetic(op, symbol, *l, *r);
+    }
+    match (left.to_big(), right.to_big()) {
+        (Some(l), Some(r)) => big_arithmetic(op, symbol, l, r),
+        _ => float_arithmetic(op, left.to_f64(), right.to_f64()),
+    }
+}
+
+fn int_arithmetic(op: &Operator, symbol: &str, l: i64, r: i64) -> Result<FddlValue, RuntimeError> {
+    let result = match op {
+        Operator::Plus => l.checked_add(r),
+        Operator::Minus => l.checked_sub(r),
+        Operator::Multiply => l.checked_mul(r),
+        Operator::Divide => return float_arithmetic(op, l as f64, r as f64),
+        Operator::Modulus if r == 0 => return Err(RuntimeError::TypeMismatch("Modulus by zero.".to_string())),
+        Operator::Modulus => floor_mod(l, r),
+        Operator::IntegerDivide if r == 0 => return Err(RuntimeError::DivisionByZero),
+        Operator::IntegerDivide => floor_div(l, r),
+        Operator::Power if r < 0 => return float_arithmetic(op, l as f64, r as f64),
+        Operator::Power => int_power(l, r),
+        _ => unreachable!("not an arithmetic operator: {:?}", op),
+    };
+    match result {
+        Some(result) => Ok(FddlValue::Int(result)),
+        // Overflowed, so redo it without a size limit
+        None => big_arithmetic(op, symbol, BigInt::from_i64(l), BigInt::from_i64(r)),
+    }
+}
+
+fn big_arithmetic(op: &Operator, symbol: &str, l: BigInt, r: BigInt) -> Result<FddlValue, RuntimeError> {
+    let result = match op {
+        Operator::Plus => l.add(&r),
+        Operator::Minus => l.sub(&r),
+        Operator::Multiply => l.mul(&r),
+        Operator::Divide if r.is_zero() => return Err(RuntimeError::DivisionByZero),
+        Operator::Divide => return Ok(FddlValue::Number(l.div_to_f64(&r))),
+        Operator::Modulus if r.is_zero() => return Err(RuntimeError::TypeMismatch("Modulus by zero.".to_string())),
+        Operator::Modulus => {
+            let remainder = l.div_rem(&r).1;
+            if !remainder.is_zero() && remainder.is_negative() != r.is_negative() {
+                remainder.add(&r)
+            } else {
+                remainder
+            }
+        }
+        Operator::IntegerDivide if r.is_zero() => return Err(RuntimeError::DivisionByZero),
+        Operator::IntegerDivide => l.div_floor(&r),
+        Operator::Power if r.is_negative() => return float_arithmetic(op, l.to_f64(), r.to_f64()),
+        Operator::Power => big_power(symbol, &l, &r)?,
+        _ => unreachable!("not an arithmetic operator: {:?}", op),
+    };
+    Ok(Number::from_big(result).to_value())
+}
+
+fn big_power(symbol: &str, base: &BigInt, exponent: &BigInt) -> Result<BigInt, RuntimeError> {
+    let one = BigInt::from_i64(1);
+    // These stay small however big the exponent is
+    if base.is_zero() || *base == one {
+        return Ok(base.clone());
+    }
+    if *base == one.neg() {
+        let odd = !exponent.div_rem(&BigInt::from_i64(2)).1.is_zero();
+        return Ok(if odd { base.clone() } else { one });
+    }
+    match exponent.to_i64().and_then(|e| u32::try_from(e).ok()) {
+        Some(e) if base.bits().saturating_mul(e as u64) <= MAX_BITS => Ok(base.pow(e)),
+        _ => Err(RuntimeError::IntegerTooLarge(format!("{} {} {}", base, symbol, exponent))),
+    }
+}
+
+// `l << r` for any shift from 0 to 63, growing past 64 bits if it has to.
+pub fn shift_left(l: i64, r: u32) -> FddlValue {
+    match l.checked_shl(r) {
+        Some(shifted) if shifted >> r == l => FddlValue::Int(shifted),
+        _ => Number::from_big(BigInt::from_i64(l).shl(r as u64)).to_value(),
+    }
+}
+
+fn float_arithmetic(op: &Operator, l: f64, r: f64) -> Result<FddlValue, RuntimeError> {
+    let result = match op {
+        Operator::Plus => l + r,
+        Operator::Minus => l - r,
+        Operator::Multiply => l * r,
+        Operator::Divide if r == 0.0 => return Err(RuntimeError::DivisionByZero),
+        Operator::Divide => l / r,
+        Operator::Modulus if r == 0.0 => return Err(RuntimeError::TypeMismatch("Modulus by zero.".to_string())),
+        // Takes the divisor's sign, like the int version
+        Operator::Modulus => {
+            let remainder = l % r;
+            if remainder != 0.0 && (remainder < 0.0) != (r < 0.0) {
+                remainder + r
+            } else {
+                remainder
+            }
+        }
+        Operator::IntegerDivide if r == 0.0 => return Err(RuntimeError::DivisionByZero),
+        // Rounds down, so `-7 div 2` is -4
+        Operator::IntegerDivide => (l / r).floor(),
+        Operator::Power => l.powf(r),
+        _ => unreachable!("not an arithmetic operator: {:?}", op),
+    };
+    Ok(FddlValue::Number(result))
+}
+
+// Division rounding towards negative infinity, to match `div` on floats.
+fn floor_div(l: i64, r: i64) -> Option<i64> {
+    let quotient = l.checked_div(r)?;
+    if l % r != 0 && (l < 0) != (r < 0) {
+        Some(quotient - 1)
+    } else {
+        Some(quotient)
+    }
+}
+
+// The remainder that goes with `floor_div`, so `(a div b) * b + a % b == a`. It
+// has the divisor's sign: `-5 % 3` is 1 and `5 % -3` is -1.
+fn floor_mod(l: i64, r: i64) -> Option<i64> {
+    let remainder = l.checked_rem(r)?;
+    if remainder != 0 && (remainder < 0) != (r < 0) {
+        Some(remainder + r)
+    } else {
+        Some(remainder)
+    }
+}
+
+fn int_power(base: i64, exponent: i64) -> Option<i64> {
+    match u32::try_from(exponent) {
+        Ok(exponent) => base.checked_pow(exponent),
+        // Only these bases survive an exponent this big
+        Err(_) => match base {
+            0 | 1 => Some(base),
+            -1 => Some(if exponent % 2 == 0 { 1 } else { -1 }),
+            _ => None,
+        },
+    }
+}
+
+pub fn negate(n: Number) -> FddlValue {
+    match n {
+        Number::Int(n) => match n.checked_neg() {
+            Some(negated) => FddlValue::Int(negated),
+            None => Number::from_big(BigInt::from_i64(n).neg()).to_value(),
+        },
+        Number::Big(n) => Number::from_big(n.neg()).to_value(),
+        Number::Float(n) => FddlValue::Number(-n),
+    }
+}
+
+// Orders two numbers exactly, even ints too big for a float to hold precisely.
+// None if either is NaN.
+pub fn compare(left: &Number, right: &Number) -> Option<Ordering> {
+    match (left, right) {
+        (Number::Int(l), Number::Int(r)) => Some(l.cmp(r)),
+        (Number::Float(l), Number::Float(r)) => l.partial_cmp(r),
+        (Number::Int(l), Number::Float(r)) => compare_int_float(*l, *r),
+        (Number::Float(l), Number::Int(r)) => compare_int_float(*r, *l).map(Ordering::reverse),
+        (Number::Big(l), Number::Float(r)) => compare_big_float(l, *r),
+        (Number::Float(l), Number::Big(r)) => compare_big_float(r, *l).map(Ordering::reverse),
+        _ => Some(left.to_big().cmp(&right.to_big())),
+    }
+}
+
+fn compare_big_float(big: &BigInt, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        return None;
+    }
+    let whole = float.trunc();
+    match BigInt::from_f64(whole) {
+        Some(whole_big) => match big.cmp(&whole_big) {
+            Ordering::Equal => 0.0.partial_cmp(&(float - whole)),
+            ordering => Some(ordering),
+        },
+        // Infinite
+        None => Some(if float > 0.0 { Ordering::Less } else { Ordering::Greater }),
+    }
+}
+
+fn compare_int_float(int: i64, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        return None;
+    }
+    if float >= i64::MAX as f64 {
+        return Some(Ordering::Less); // i64::MAX as f64 rounds up to 2^63
+    }
+    if float < i64::MIN as f64 {
+        return Some(Ordering::Greater);
+    }
+    let whole = float.trunc();
+    match int.cmp(&(whole as i64)) {
+        // Same whole part, so the fraction decides
+        Ordering::Equal => 0.0.partial_cmp(&(float - whole)),
+        ordering => Some(ordering),
+    }
+}
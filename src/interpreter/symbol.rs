@@ -0,0 +1,48 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// An interned name, written `sym name` in fddl. Every symbol with the same name is the
+// same small id, so comparing or hashing them never looks at the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn intern(name: &str) -> Self {
+        INTERNER.with(|interner| interner.borrow_mut().intern(name))
+    }
+
+    pub fn name(&self) -> Rc<str> {
+        INTERNER.with(|interner| Rc::clone(&interner.borrow().names[self.0 as usize]))
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, ":{}", self.name())
+    }
+}
+
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<Rc<str>, Symbol>,
+    names: Vec<Rc<str>>,
+}
+
+impl Interner {
+    fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.ids.get(name) {
+            return *symbol;
+        }
+        let symbol = Symbol(self.names.len() as u32);
+        let name: Rc<str> = Rc::from(name);
+        self.names.push(Rc::clone(&name));
+        self.ids.insert(name, symbol);
+        symbol
+    }
+}
+
+// The evaluator is single threaded, so one interner per thread is enough.
+thread_local! {
+    static INTERNER: RefCell<Interner> = RefCell::new(Interner::default());
+}
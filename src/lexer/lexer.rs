@@ -1,42 +1,83 @@
-use crate::lexer::token::Token;
+use crate::lexer::token::{Span, SpannedToken, TemplatePart, Token};
 
-#[allow(dead_code)]
 pub struct Lexer {
     source: Vec<char>,
+    // Byte offset of each char in `source`, plus one trailing entry for the end of input.
+    byte_offsets: Vec<usize>,
     start: usize,
     current: usize,
     line: usize,
+    line_start: usize, // index of the first char on the current line
+    start_line: usize,
+    start_column: usize,
 }
 
 impl Lexer {
     pub fn new(source: String) -> Self {
+        let mut byte_offsets: Vec<usize> = source.char_indices().map(|(offset, _)| offset).collect();
+        byte_offsets.push(source.len());
+
         Lexer {
             source: source.chars().collect(),
+            byte_offsets,
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            start_line: 1,
+            start_column: 1,
         }
     }
 
     pub fn scan_tokens(&mut self) -> Vec<Token> {
+        self.scan_spanned_tokens()
+            .into_iter()
+            .map(|spanned| spanned.token)
+            .collect()
+    }
+
+    pub fn scan_spanned_tokens(&mut self) -> Vec<SpannedToken> {
         let mut tokens = Vec::new();
 
         while !self.is_at_end() {
-            self.start = self.current;
+            self.begin_token();
             if let Some(token) = self.scan_token() {
-                if matches!(token, Token::Error(_)) {
-                    tokens.push(token);
+                let is_error = matches!(token, Token::Error(_));
+                tokens.push(SpannedToken::new(token, self.current_span()));
+                if is_error {
                     break;
-                } else {
-                    tokens.push(token);
                 }
             }
         }
 
-        tokens.push(Token::EOF);
+        self.begin_token();
+        tokens.push(SpannedToken::new(Token::EOF, self.current_span()));
         tokens
     }
 
+    // Marks the current position as the start of the next token.
+    fn begin_token(&mut self) {
+        self.start = self.current;
+        self.start_line = self.line;
+        self.start_column = self.current - self.line_start + 1;
+    }
+
+    // Span of the token currently being scanned (from `start` up to `current`).
+    fn current_span(&self) -> Span {
+        Span::new(
+            self.byte_offsets[self.start],
+            self.byte_offsets[self.current],
+            self.start_line,
+            self.start_column,
+        )
+    }
+
+    // Called right after a '\n' has been consumed.
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.line_start = self.current;
+    }
+
     fn scan_token(&mut self) -> Option<Token> {
         let c = self.advance();
 
@@ -46,13 +87,29 @@ impl Lexer {
             ')' => Some(Token::RightParen),
             '{' => Some(Token::LeftBrace),
             '}' => Some(Token::RightBrace),
+            '[' => Some(Token::LeftBracket),
+            ']' => Some(Token::RightBracket),
             ',' => Some(Token::Comma),
-            '.' => Some(Token::Dot),
-            '-' => Some(Token::Minus),
-            '+' => Some(Token::Plus),
+            ':' => Some(Token::Colon),
+            '.' => {
+                if self.match_char('.') {
+                    if self.match_char('=') {
+                        Some(Token::DotDotEqual)
+                    } else {
+                        Some(Token::DotDot)
+                    }
+                } else {
+                    Some(Token::Dot)
+                }
+            },
+            '-' => Some(if self.match_char('=') { Token::MinusEqual } else { Token::Minus }),
+            '+' => Some(if self.match_char('=') { Token::PlusEqual } else { Token::Plus }),
             ';' => Some(Token::Semicolon),
-            '*' => Some(Token::Star),
-            '%' => Some(Token::Percent),
+            '*' => Some(if self.match_char('=') { Token::StarEqual } else { Token::Star }),
+            '%' => Some(if self.match_char('=') { Token::PercentEqual } else { Token::Percent }),
+            '^' => Some(Token::Caret),
+            '&' => Some(Token::Ampersand),
+            '|' => Some(Token::Pipe),
             '~' => {
                 if self.match_char('=') {
                     Some(Token::TildeEqual)
@@ -71,6 +128,8 @@ impl Lexer {
                     Some(Token::Comment(comment_text)) // Or None if you want to skip comments
                 } else if self.match_char('*') { // For block comments /* ... */
                     self.consume_block_comment() // Call a new helper function
+                } else if self.match_char('=') {
+                    Some(Token::SlashEqual)
                 } else {
                     Some(Token::Slash) // Division operator
                 }
@@ -95,6 +154,8 @@ impl Lexer {
             '=' => {
                 if self.match_char('=') {
                     Some(Token::EqualEqual)
+                } else if self.match_char('>') {
+                    Some(Token::FatArrow)
                 } else {
                     Some(Token::Equal)
                 }
@@ -102,6 +163,8 @@ impl Lexer {
             '<' => {
                 if self.match_char('=') {
                     Some(Token::LessEqual)
+                } else if self.match_char('<') {
+                    Some(Token::LessLess)
                 } else {
                     Some(Token::Less)
                 }
@@ -109,6 +172,8 @@ impl Lexer {
             '>' => {
                 if self.match_char('=') {
                     Some(Token::GreaterEqual)
+                } else if self.match_char('>') {
+                    Some(Token::GreaterGreater)
                 } else {
                     Some(Token::Greater)
                 }
@@ -117,34 +182,28 @@ impl Lexer {
             // Whitespace
             ' ' | '\r' | '\t' => None,
             '\n' => {
-                self.line += 1;
+                self.new_line();
                 None
             },
 
             // Literals
             '"' => self.string(),
+            '`' => self.template(),
+            'r' if self.is_raw_string_start() => self.raw_string(),
             c if c.is_ascii_digit() => self.number(),
             c if self.is_alpha(c) => self.identifier(),
 
             // Any other character
-            _ => {
-                eprintln!("Unexpected character '{}' on line {}", c, self.line);
-                Some(Token::Error(format!("Unexpected character '{}'", c)))
-            }
+            _ => Some(Token::Error(format!("Unexpected character '{}'", c))),
         }
     }
 
     fn consume_block_comment(&mut self) -> Option<Token> {
         let mut comment_text = String::new();
-        let start_line = self.line;
 
         loop {
             if self.is_at_end() {
-                eprintln!("Error (L{}): Unterminated block comment", start_line);
-                if !comment_text.is_empty() {
-                    return Some(Token::Comment(comment_text));
-                }
-                return None;
+                return Some(Token::Error("Unterminated block comment.".to_string()));
             }
 
             let current_char = self.peek();
@@ -153,11 +212,10 @@ impl Lexer {
                 self.advance(); // Consume '/'
                 return Some(Token::Comment(comment_text));
             } else {
+                comment_text.push(self.advance());
                 if current_char == '\n' {
-                    self.line += 1;
+                    self.new_line();
                 }
-                comment_text.push(self.advance()); 
-
             }
         }
     }
@@ -205,49 +263,320 @@ impl Lexer {
     }
 
     fn string(&mut self) -> Option<Token> {
+        let mut value = String::new();
+        let mut error: Option<String> = None;
+
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            let c = self.advance();
+            match c {
+                '\n' => {
+                    self.new_line();
+                    value.push(c);
+                }
+                '\\' => match self.escape_sequence() {
+                    Ok(escaped) => value.push(escaped),
+                    // Keep going to the closing quote so the error covers the whole literal.
+                    Err(message) => {
+                        error.get_or_insert(message);
+                    }
+                },
+                _ => value.push(c),
             }
-            self.advance();
         }
 
         if self.is_at_end() {
-            eprintln!("Unterminated string on line {}", self.line);
-            return None;
+            return Some(Token::Error("Unterminated string.".to_string()));
         }
 
         // Consume the closing quote
         self.advance();
 
-        // Extract the string value
-        let value: String = self.source[self.start + 1..self.current - 1]
-            .iter()
-            .collect();
-        Some(Token::StringLiteral(value))
+        match error {
+            Some(message) => Some(Token::Error(message)),
+            None => Some(Token::StringLiteral(value)),
+        }
+    }
+
+    // Called with the backslash already consumed.
+    fn escape_sequence(&mut self) -> Result<char, String> {
+        if self.is_at_end() {
+            return Err("Unterminated string.".to_string());
+        }
+
+        match self.advance() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            'u' => self.unicode_escape(),
+            '\n' => {
+                self.new_line();
+                Err("Invalid escape sequence '\\' at end of line.".to_string())
+            }
+            other => Err(format!("Invalid escape sequence '\\{}'.", other)),
+        }
+    }
+
+    // Parses the `{XXXX}` part of a `\u{XXXX}` escape (1 to 6 hex digits).
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        if !self.match_char('{') {
+            return Err("Expected '{' after '\\u' in unicode escape.".to_string());
+        }
+
+        let mut digits = String::new();
+        while self.peek().is_ascii_hexdigit() {
+            digits.push(self.advance());
+        }
+
+        if !self.match_char('}') {
+            return Err("Expected '}' to close unicode escape.".to_string());
+        }
+        if digits.is_empty() || digits.len() > 6 {
+            return Err(format!("Unicode escape '\\u{{{}}}' must have 1 to 6 hex digits.", digits));
+        }
+
+        let code = u32::from_str_radix(&digits, 16).unwrap();
+        char::from_u32(code)
+            .ok_or_else(|| format!("'\\u{{{}}}' is not a valid unicode character.", digits))
+    }
+
+    // Backtick templates: `text ${expression} $name`. Escapes work as in strings,
+    // plus \` and \$ for a literal backtick or dollar sign.
+    fn template(&mut self) -> Option<Token> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut error: Option<String> = None;
+
+        loop {
+            if self.is_at_end() {
+                return Some(Token::Error("Unterminated template string.".to_string()));
+            }
+
+            let c = self.advance();
+            match c {
+                '`' => break,
+                '\n' => {
+                    self.new_line();
+                    literal.push(c);
+                }
+                '\\' if self.match_char('`') => literal.push('`'),
+                '\\' if self.match_char('$') => literal.push('$'),
+                '\\' => match self.escape_sequence() {
+                    Ok(escaped) => literal.push(escaped),
+                    Err(message) => {
+                        error.get_or_insert(message);
+                    }
+                },
+                '$' if self.match_char('{') => {
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    match self.template_expression() {
+                        Ok(tokens) => parts.push(TemplatePart::Expression(tokens)),
+                        Err(message) => return Some(Token::Error(message)),
+                    }
+                }
+                '$' if self.is_alpha(self.peek()) => {
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(TemplatePart::Expression(self.template_name()));
+                }
+                _ => literal.push(c),
+            }
+        }
+
+        if let Some(message) = error {
+            return Some(Token::Error(message));
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Some(Token::Template(parts))
+    }
+
+    // Lexes the tokens of a `${...}` interpolation, up to its closing '}'. Names in
+    // it can be written with or without the `$`.
+    fn template_expression(&mut self) -> Result<Vec<SpannedToken>, String> {
+        let outer = (self.start, self.start_line, self.start_column);
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+
+        let result = loop {
+            if self.is_at_end() {
+                break Err("Unterminated template interpolation.".to_string());
+            }
+
+            self.begin_token();
+            if depth == 0 && self.peek() == '}' {
+                tokens.push(SpannedToken::new(Token::EOF, self.current_span()));
+                self.advance();
+                break Ok(tokens);
+            }
+
+            // `$name` means the same here as it does in the template's text.
+            let token = if self.peek() == '$' && self.is_alpha(self.peek_next()) {
+                self.advance();
+                self.begin_token();
+                self.advance();
+                self.identifier()
+            } else {
+                self.scan_token()
+            };
+
+            match token {
+                Some(Token::Error(message)) => break Err(message),
+                Some(Token::Comment(_)) | None => {}
+                Some(token) => {
+                    match token {
+                        Token::LeftBrace => depth += 1,
+                        Token::RightBrace => depth -= 1,
+                        _ => {}
+                    }
+                    tokens.push(SpannedToken::new(token, self.current_span()));
+                }
+            }
+        };
+
+        (self.start, self.start_line, self.start_column) = outer;
+        result
+    }
+
+    // Lexes the name in a `$name` interpolation, with the '$' already consumed.
+    fn template_name(&mut self) -> Vec<SpannedToken> {
+        let outer = (self.start, self.start_line, self.start_column);
+
+        self.begin_token();
+        self.advance();
+        let name = self.identifier().unwrap_or(Token::EOF);
+        let name_span = self.current_span();
+        self.begin_token();
+        let tokens = vec![
+            SpannedToken::new(name, name_span),
+            SpannedToken::new(Token::EOF, self.current_span()),
+        ];
+
+        (self.start, self.start_line, self.start_column) = outer;
+        tokens
+    }
+
+    // `r` followed by zero or more '#' and a '"', as in r"..." or r#"..."#.
+    fn is_raw_string_start(&self) -> bool {
+        let mut index = self.current;
+        while index < self.source.len() && self.source[index] == '#' {
+            index += 1;
+        }
+        index < self.source.len() && self.source[index] == '"'
+    }
+
+    // Raw strings take their contents verbatim. The closing quote must be followed by
+    // as many '#' as the opening one, so r#"say "hi""# can contain quotes.
+    fn raw_string(&mut self) -> Option<Token> {
+        let mut hashes = 0;
+        while self.match_char('#') {
+            hashes += 1;
+        }
+        self.advance(); // Consume the opening quote
+
+        let mut value = String::new();
+        loop {
+            if self.is_at_end() {
+                return Some(Token::Error("Unterminated raw string.".to_string()));
+            }
+
+            let c = self.advance();
+            if c == '"' && self.closes_raw_string(hashes) {
+                self.current += hashes;
+                return Some(Token::StringLiteral(value));
+            }
+            if c == '\n' {
+                self.new_line();
+            }
+            value.push(c);
+        }
+    }
+
+    fn closes_raw_string(&self, hashes: usize) -> bool {
+        (0..hashes).all(|offset| self.source.get(self.current + offset) == Some(&'#'))
     }
 
     // Function to handle number literals
+    // `42` is an integer and `4.2` or `1e-9` a float; `0x`, `0b` and `0o` prefixes
+    // give hex, binary and octal integers, and `_` can separate digits (`1_000`).
+    // A '.' only starts a fraction when a digit follows it, so `0..10` is a range
+    // and `xs.len` a member access.
+    //
+    // Letters and underscores right after a number are taken in as part of it, so
+    // `0b102` or `12px` is reported as one bad literal rather than split in two.
     fn number(&mut self) -> Option<Token> {
-        while self.peek().is_ascii_digit() {
+        let radix = match (self.source[self.start], self.peek()) {
+            ('0', 'x' | 'X') => 16,
+            ('0', 'b' | 'B') => 2,
+            ('0', 'o' | 'O') => 8,
+            _ => 10,
+        };
+        if radix != 10 {
             self.advance();
         }
-
-        // Look for a fractional part
-        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
-            // Consume the '.'
-            self.advance();
-
-            while self.peek().is_ascii_digit() {
+        let digits_start = self.current - if radix == 10 { 1 } else { 0 };
+        self.number_part(radix);
+        let mut parts = vec![(digits_start, self.current)];
+
+        let mut is_float = false;
+        if radix == 10 {
+            // Look for a fractional part
+            if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+                // Consume the '.'
                 self.advance();
+                is_float = true;
+                let fraction_start = self.current;
+                self.number_part(radix);
+                parts.push((fraction_start, self.current));
+            }
+            // And an exponent
+            if matches!(self.peek(), 'e' | 'E') {
+                self.advance();
+                is_float = true;
+                if matches!(self.peek(), '+' | '-') && self.peek_next().is_ascii_digit() {
+                    self.advance();
+                }
+                let exponent_start = self.current;
+                self.number_part(radix);
+                parts.push((exponent_start, self.current));
             }
         }
 
-        let value_str: String = self.source[self.start..self.current]
-            .iter()
-            .collect();
-        let value = value_str.parse::<f64>().unwrap();
-        Some(Token::Number(value))
+        let literal: String = self.source[self.start..self.current].iter().collect();
+        for (start, end) in parts {
+            let digits: String = self.source[start..end].iter().collect();
+            if let Err(message) = check_digits(&digits, radix, &literal) {
+                return Some(Token::Error(message));
+            }
+        }
+
+        let value_str = literal.replace('_', "");
+        if is_float {
+            return match value_str.parse::<f64>() {
+                Ok(value) if value.is_finite() => Some(Token::Number(value)),
+                _ => Some(Token::Error(format!("Number literal '{}' is too large for a float.", literal))),
+            };
+        }
+        let digits = if radix == 10 { &value_str[..] } else { &value_str[2..] };
+        match i64::from_str_radix(digits, radix) {
+            Ok(value) => Some(Token::Integer(value)),
+            // The digits are all valid by now, so the literal is just too big
+            Err(_) => Some(Token::BigInteger(value_str.to_lowercase())),
+        }
+    }
+
+    // Consumes the run of letters, digits and underscores that makes up one part of
+    // a number literal. In decimal an 'e' ends the part, since it starts an exponent.
+    fn number_part(&mut self, radix: u32) {
+        while self.is_alphanumeric(self.peek()) && !(radix == 10 && matches!(self.peek(), 'e' | 'E')) {
+            self.advance();
+        }
     }
 
     // Function to handle identifiers and keywords
@@ -280,6 +609,10 @@ impl Lexer {
             "sym" => Token::Sym,
             "module" => Token::Module,
             "import" => Token::Import,
+            "in" => Token::In,
+            "break" => Token::Break,
+            "continue" => Token::Continue,
+            "div" => Token::Div,
             "some" => Token::Some,
             "not" => Token::Not,
             _ => Token::Identifier(text),
@@ -308,3 +641,24 @@ impl Lexer {
     // DEAD CODE? 
 }
 
+// Checks one part of a number literal (the digits before or after a '.', or of an
+// exponent): it can't be empty, every character must be a digit in `radix` or a
+// `_`, and each `_` must sit between two digits.
+fn check_digits(digits: &str, radix: u32, literal: &str) -> Result<(), String> {
+    if digits.is_empty() {
+        return Err(format!("Number literal '{}' is missing digits.", literal));
+    }
+    if let Some(c) = digits.chars().find(|&c| c != '_' && !c.is_digit(radix)) {
+        let kind = match radix {
+            2 => "binary",
+            8 => "octal",
+            16 => "hex",
+            _ => "decimal",
+        };
+        return Err(format!("'{}' is not a valid {} digit in number literal '{}'.", c, kind, literal));
+    }
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return Err(format!("'_' in number literal '{}' must be between two digits.", literal));
+    }
+    Ok(())
+}
@@ -5,28 +5,47 @@ pub enum Token {
     RightParen,   // )
     LeftBrace,    // {
     RightBrace,   // }
+    LeftBracket,  // [
+    RightBracket, // ]
     Comma,        // ,
+    Colon,        // :
     Dot,          // .
+    DotDot,       // ..
+    DotDotEqual,  // ..=
     Minus,        // -
     Plus,         // +
     Semicolon,    // ;
     Slash,        // /
     Star,         // *
     Percent,      // %
+    Caret,        // ^
+    Ampersand,    // &
+    Pipe,         // |
     Equal,        // =
+    PlusEqual,    // +=
+    MinusEqual,   // -=
+    StarEqual,    // *=
+    SlashEqual,   // /=
+    PercentEqual, // %=
     BangEqual,    // !=
     EqualEqual,   // ==
+    FatArrow,     // =>
     Greater,      // >
     GreaterEqual, // >=
     Less,         // <
     LessEqual,    // <=
+    LessLess,     // <<
+    GreaterGreater, // >>
     Tilde,        // ~
     TildeEqual,   // ~=
 
     // Literals
     Identifier(String),
     StringLiteral(String),
-    Number(f64),
+    Template(Vec<TemplatePart>), // `text ${expr} $name`
+    Integer(i64), // 42
+    BigInteger(String), // 100000000000000000000 or 0xffffffffffffffff: an int too big for an i64, without '_'s
+    Number(f64),  // 4.2
 
     // Keywords
     And,
@@ -47,6 +66,10 @@ pub enum Token {
     Sym,
     Module,
     Import,
+    In,
+    Break,
+    Continue,
+    Div,
     Some,
     Not,
 
@@ -58,3 +81,57 @@ pub enum Token {
 
     EOF,
 }
+
+// A piece of a backtick template. Interpolated expressions are lexed up front, so
+// their tokens carry spans into the original source; each list ends with an EOF.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TemplatePart {
+    Literal(String),
+    Expression(Vec<SpannedToken>),
+}
+
+// Where a piece of source text lives. `start` and `end` are byte offsets
+// (end exclusive); `line` and `column` are 1-based and point at `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span { start, end, line, column }
+    }
+
+    // Returns a span covering everything from the start of `self` to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end.max(self.end),
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+// A token together with the source location it was scanned from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+impl SpannedToken {
+    pub fn new(token: Token, span: Span) -> Self {
+        SpannedToken { token, span }
+    }
+}
+
+// Lets bare tokens (e.g. hand-written ones in tests) be used where spanned ones are expected.
+impl From<Token> for SpannedToken {
+    fn from(token: Token) -> Self {
+        SpannedToken { token, span: Span::default() }
+    }
+}
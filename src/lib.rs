@@ -1,4 +1,5 @@
 pub mod lexer;
 pub mod parser;
 pub mod compiler;
-pub mod interpreter;
\ No newline at end of file
+pub mod interpreter;
+pub mod diagnostics;
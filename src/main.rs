@@ -1,31 +1,101 @@
-mod lexer;
-mod parser;
-mod compiler;
-
 use std::env;
 // use std::fs;
 use std::io::Write;
 
 use fddl::lexer::Lexer;
 use fddl::parser::parser::Parser;
-use fddl::interpreter::evaluator::Evaluator;
+use fddl::parser::resolver::resolve;
+use fddl::interpreter::almost::AlmostMode;
+use fddl::interpreter::evaluator::{Evaluator, STACK_SIZE};
 use fddl::parser::ast::Statement;
+use fddl::diagnostics::{render_json, render_plain, Diagnostic};
+
+// How errors get printed: for people (default) or as JSON (`--json`) for CI.
+#[derive(Clone, Copy, PartialEq)]
+enum ErrorFormat {
+    Plain,
+    Json,
+}
+
+// Settings from the command line that apply to every evaluator we create.
+#[derive(Clone, Copy)]
+struct Options {
+    error_format: ErrorFormat,
+    almost_mode: AlmostMode,
+}
+
+const USAGE: &str = "usage: fddl [--json] [--almost bithash|seeded|off] [--seed N] [--almost-magnitude X] [file]";
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args = env::args().skip(1);
+
+    let mut error_format = ErrorFormat::Plain;
+    let mut almost: Option<String> = None;
+    let mut seed: u64 = 0;
+    let mut magnitude: f64 = 0.1;
+    let mut path: Option<String> = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => error_format = ErrorFormat::Json,
+            "--almost" => almost = Some(flag_value(&arg, args.next())),
+            "--seed" => {
+                seed = parse_flag(&arg, args.next());
+                // A seed on its own means "use the seeded mode".
+                almost.get_or_insert_with(|| "seeded".to_string());
+            }
+            "--almost-magnitude" => magnitude = parse_flag(&arg, args.next()),
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option '{}'", arg)),
+            _ => path = Some(arg),
+        }
+    }
 
-    // runs file or REPL
-    if args.len() > 1 {
-        run_file(&args[1]);
-    } else {
-        run_repl();
+    let almost_mode = match almost.as_deref() {
+        None | Some("bithash") => AlmostMode::BitHash,
+        Some("seeded") => AlmostMode::Seeded { seed, magnitude },
+        Some("off") => AlmostMode::Off,
+        Some(other) => usage_error(&format!("unknown --almost mode '{}'", other)),
+    };
+    let options = Options { error_format, almost_mode };
+
+    // runs file or REPL, on a thread with room for MAX_CALL_DEPTH nested calls
+    let interpreter = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || match path {
+            Some(path) => run_file(&path, options),
+            None => run_repl(options),
+        })
+        .expect("failed to start the interpreter thread");
+    if interpreter.join().is_err() {
+        std::process::exit(101);
     }
 }
 
-fn run_repl() {
+fn flag_value(flag: &str, value: Option<String>) -> String {
+    value.unwrap_or_else(|| usage_error(&format!("'{}' needs a value", flag)))
+}
+
+fn parse_flag<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    let value = flag_value(flag, value);
+    value
+        .parse()
+        .unwrap_or_else(|_| usage_error(&format!("invalid value '{}' for '{}'", value, flag)))
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("error: {}\n{}", message, USAGE);
+    std::process::exit(2);
+}
+
+fn new_evaluator(options: Options) -> Evaluator {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_almost_mode(options.almost_mode);
+    evaluator
+}
+
+fn run_repl(options: Options) {
     println!("fddl REPL");
     println!("---------");
-    let mut evaluator = Evaluator::new();
+    let mut evaluator = new_evaluator(options);
 
     loop {
         print!("fddl % "); 
@@ -40,18 +110,33 @@ fn run_repl() {
             continue;
         }
 
-        run_line(buffer, &mut evaluator); 
+        run_line(buffer, "<repl>", &mut evaluator, options.error_format); 
     }
 }
 
-fn run_line(source: String, evaluator: &mut Evaluator) { 
+fn run_line(source: String, file_name: &str, evaluator: &mut Evaluator, error_format: ErrorFormat) { 
     println!("Source: {}", source.trim());
 
-    let mut lexer = Lexer::new(source);
-    let tokens = lexer.scan_tokens();
+    let mut lexer = Lexer::new(source.clone());
+    let tokens = lexer.scan_spanned_tokens();
 
     let mut parser = Parser::new(tokens);
-    let program_ast: Vec<Statement> = parser.parse_program();
+    let program_ast: Vec<Statement> = match parser.parse_program() {
+        Ok(statements) => statements,
+        Err(errors) => {
+            let diagnostics: Vec<Diagnostic> = errors.iter().map(Diagnostic::from_parse_error).collect();
+            report(&diagnostics, &source, file_name, error_format);
+            println!("---");
+            return;
+        }
+    };
+
+    if let Err(errors) = resolve(&program_ast) {
+        let diagnostics: Vec<Diagnostic> = errors.iter().map(Diagnostic::from_parse_error).collect();
+        report(&diagnostics, &source, file_name, error_format);
+        println!("---");
+        return;
+    }
 
     if !program_ast.is_empty() {
 
@@ -59,21 +144,34 @@ fn run_line(source: String, evaluator: &mut Evaluator) {
         match evaluator.evaluate_program(program_ast) { 
             Ok(()) => { /* Statement executed successfully */ }
             Err(e) => {
-                eprintln!("Runtime Error: {:?}", e);
+                let diagnostic = Diagnostic::from_runtime_error(&e, evaluator.last_error_span());
+                report(&[diagnostic], &source, file_name, error_format);
             }
         }
     } else {
-        println!("No AST generated or parsing failed for this line.");
+        println!("No AST generated for this line.");
     }
     println!("---");
 }
 
-fn run_file(path: &str) {
+fn report(diagnostics: &[Diagnostic], source: &str, file_name: &str, error_format: ErrorFormat) {
+    match error_format {
+        ErrorFormat::Plain => {
+            for diagnostic in diagnostics {
+                eprint!("{}", render_plain(diagnostic, source, file_name));
+            }
+        }
+        ErrorFormat::Json => eprintln!("{}", render_json(diagnostics, file_name)),
+    }
+}
+
+fn run_file(path: &str, options: Options) {
     println!("Running file: {}", path);
     match std::fs::read_to_string(path) {
         Ok(source) => {
-            let mut file_evaluator = Evaluator::new();
-            run_line(source, &mut file_evaluator);
+            let mut file_evaluator = new_evaluator(options);
+            file_evaluator.set_current_file(path);
+            run_line(source, path, &mut file_evaluator, options.error_format);
         }
         Err(e) => {
             eprintln!("Error reading file '{}': {}", path, e);
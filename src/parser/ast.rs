@@ -1,21 +1,77 @@
-#[allow(dead_code)]
+use crate::lexer::token::Span;
+
+// Every node carries the span of source it was parsed from. Spans are left out
+// of equality so that two trees with the same shape compare equal no matter
+// where they came from.
+#[derive(Debug, Clone)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Expression { kind, span }
+    }
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl From<ExpressionKind> for Expression {
+    fn from(kind: ExpressionKind) -> Self {
+        Expression { kind, span: Span::default() }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Expression {
+pub enum ExpressionKind {
     Literal(Literal),
     Variable(String),
     Binary(Box<Expression>, Operator, Box<Expression>),
     Unary(Operator, Box<Expression>),
     Grouping(Box<Expression>),
-    Assignment(String, Box<Expression>),
+    // `target = value`, or `target += value` etc. with the operator set; evaluates to the stored value
+    Assignment {
+        target: Box<AssignTarget>,
+        operator: Option<Operator>,
+        value: Box<Expression>,
+    },
     FunctionCall(Box<Expression>, Vec<Expression>),
+    Interpolation(Vec<InterpolationPart>), // `a ${b} c`
+    Get(Box<Expression>, String),          // module.member
+    List(Vec<Expression>),                 // [a, b, c]
+    Index(Box<Expression>, Box<Expression>), // list[index] or map[key]
+    Map(Vec<(Expression, Expression)>),    // { "a": 1, b: 2 }, in insertion order
+    // func (a, b) { ... } or func (x) => x * 2
+    Function {
+        params: Vec<String>,
+        body: Vec<Statement>,
+    },
+    Range {
+        start: Box<Expression>,
+        end: Box<Expression>,
+        inclusive: bool, // `..=` rather than `..`
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpolationPart {
+    Literal(String),
+    Expression(Expression),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
+    Int(i64),
+    BigInt(String), // An int literal too big for an i64, as BigInt::parse reads it
     Number(f64),
     String(String),
     Boolean(bool),
+    Symbol(String), // sym name
     Nil,
 }
 
@@ -35,6 +91,15 @@ pub enum Operator {
     Multiply, // For multiplication e.g. 5 * 5
     Divide, // For division e.g. 5 / 5
     Modulus, // For modulus e.g. 5 % 5
+    Power, // For exponentiation e.g. 2 ^ 10
+    IntegerDivide, // For floored division e.g. 7 div 2
+
+    // Bitwise operators, on whole numbers only
+    BitAnd,     // 6 & 3
+    BitOr,      // 6 | 3
+    BitXor,     // 6 ~ 3
+    ShiftLeft,  // 1 << 4
+    ShiftRight, // 16 >> 2
 
     // Comparison operators
     Greater,
@@ -49,26 +114,87 @@ pub enum Operator {
     // Equality (we'll add these logic for these later)
     EqualEqual, // For equality e.g. 5 == 5
     NotEqual, // For inequality e.g. 5 != 5
-    // AlmostEqual, // For ~= (binary tilde-equal) 🙃
+    AlmostEqual, // For ~= (binary tilde-equal) 🙃
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+impl Statement {
+    pub fn new(kind: StatementKind, span: Span) -> Self {
+        Statement { kind, span }
+    }
+}
+
+impl PartialEq for Statement {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl From<StatementKind> for Statement {
+    fn from(kind: StatementKind) -> Self {
+        Statement { kind, span: Span::default() }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Statement {
+pub enum StatementKind {
     ExpressionStatement(Expression),
     PrintStatement(Expression),
     VariableDeclaration(String, Option<Expression>),
+    ConstDeclaration(String, Expression),
     Block(Vec<Statement>),
     IfStatement(Expression, Box<Statement>, Option<Box<Statement>>),
     WhileStatement(Expression, Box<Statement>),
     ForStatement(Box<Statement>, Expression, Box<Statement>, Box<Statement>),
+    // for (item in iterable) or for (key, value in iterable)
+    ForInStatement {
+        variables: Vec<String>, // One or two names
+        iterable: Expression,
+        body: Box<Statement>,
+    },
     FunctionDeclaration {
         name: String,
         params: Vec<String>,
         body: Vec<Statement>,
     },
+    BreakStatement,
+    ContinueStatement,
     ReturnStatement(Option<Expression>),
-    Assignment {
-        target_name: String,
-        value: Expression,
+    ModuleDeclaration {
+        name: String,
+        body: Vec<Statement>,
+    },
+    Public(Box<Statement>), // `pub` in front of a let, const, func or module declaration
+    Import {
+        path: String, // As written, e.g. "lib/math" for `import "lib/math"` or "math" for `import math`
+        name: String, // What the module is bound to: the file name without its extension
     },
+}
+
+// What can appear on the left of `=`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignTarget {
+    Variable(String),
+    Index { object: Expression, index: Expression }, // list[index] = value or map[key] = value
+    Field { object: Expression, name: String },      // map.name = value
+}
+
+impl StatementKind {
+    // The name a declaration introduces, if it is one.
+    pub fn declared_name(&self) -> Option<&str> {
+        match self {
+            StatementKind::VariableDeclaration(name, _)
+            | StatementKind::ConstDeclaration(name, _)
+            | StatementKind::FunctionDeclaration { name, .. }
+            | StatementKind::ModuleDeclaration { name, .. }
+            | StatementKind::Import { name, .. } => Some(name),
+            StatementKind::Public(inner) => inner.kind.declared_name(),
+            _ => None,
+        }
+    }
 }
\ No newline at end of file
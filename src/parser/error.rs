@@ -0,0 +1,58 @@
+use crate::lexer::token::{Span, Token};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedToken,         // A token showed up where it doesn't belong
+    UnexpectedEof,           // The source ended in the middle of a construct
+    InvalidAssignmentTarget, // Left-hand side of '=' can't be assigned to
+    LexError,                // The lexer handed us a Token::Error
+    AssignToConstant,        // Found by the resolver: assignment to a `const`
+    MisplacedPub,            // Found by the resolver: `pub` inside a block or function
+    OutsideLoop,             // `break` or `continue` that isn't inside a loop
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub message: String,
+    pub span: Span,
+    pub expected: Vec<Token>, // Tokens that would have been accepted here (may be empty)
+    pub found: Option<Token>, // The token we actually got, if any
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, message: impl Into<String>, span: Span) -> Self {
+        ParseError {
+            kind,
+            message: message.into(),
+            span,
+            expected: Vec::new(),
+            found: None,
+        }
+    }
+
+    pub fn with_expected(mut self, expected: Vec<Token>) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    pub fn with_found(mut self, found: Token) -> Self {
+        self.found = Some(found);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[line {}, column {}] {}", self.span.line, self.span.column, self.message)?;
+        if let Some(found) = &self.found {
+            write!(f, " Found {:?}.", found)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
@@ -1,5 +1,9 @@
 pub mod ast;
+pub mod error;
+#[allow(clippy::module_inception)]
 pub mod parser;
+pub mod resolver;
 
-// pub use ast::*;
-// pub use parser::*;
\ No newline at end of file
+pub use parser::Parser;
+
+// pub use ast::*;
\ No newline at end of file
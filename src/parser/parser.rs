@@ -1,117 +1,264 @@
-use crate::lexer::token::Token;
-use crate::parser::ast::{Expression, Statement, Literal, Operator}; 
+use crate::lexer::token::{Span, SpannedToken, TemplatePart, Token};
+use crate::parser::ast::{AssignTarget, Expression, ExpressionKind, InterpolationPart, Statement, StatementKind, Literal, Operator};
+use crate::parser::error::{ParseError, ParseErrorKind, ParseResult};
 // use crate::lexer::Lexer;
 
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<SpannedToken>,
     current: usize,
+    errors: Vec<ParseError>, // Errors recovered from so far
+    loop_depth: usize,       // How many loops enclose the current statement, within the current function
 }
 
 #[allow(dead_code)]
 impl Parser {
 
-    pub fn new(tokens: Vec<Token>) -> Self {
+    // Accepts either plain tokens or tokens with spans (from `Lexer::scan_spanned_tokens`).
+    pub fn new<T: Into<SpannedToken>>(tokens: Vec<T>) -> Self {
         Parser {
-            tokens,
+            tokens: tokens.into_iter().map(Into::into).collect(),
             current: 0,
+            errors: Vec::new(),
+            loop_depth: 0,
         }
     }
 
-    fn parse_assignment_or_expression_statement(&mut self) -> Option<Statement> {
+    fn parse_assignment_or_expression_statement(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
         let expr = self.parse_expression()?;
 
         self.skip_comments(); // Skip comments before semicolon
 
-        if self.check(&Token::Equal) {
-            self.advance();
-
-            match expr {
-                Expression::Variable(target_name) => {
-                    self.skip_comments();
-                    let value_expr = self.parse_expression()?;
-
-                    self.skip_comments();
-                    if !self.match_token(Token::Semicolon) {
-                        eprintln!("Error: Expected ';' after assignment.");
-                        return None;
-                    }
-                    Some(Statement::Assignment { target_name, value: value_expr })
-                }
-                _ => {
-                    eprintln!("Error: Invalid assignment target. Must be an identifier.");
-                    return None;
-                }
-            }
+        if let ExpressionKind::Assignment { .. } = expr.kind {
+            self.consume(Token::Semicolon, "Expected ';' after assignment.")?;
+            Ok(Statement::new(StatementKind::ExpressionStatement(expr), self.span_from(start_span)))
         } else if self.match_token(Token::Semicolon) {
-            Some(Statement::ExpressionStatement(expr))
+            Ok(Statement::new(StatementKind::ExpressionStatement(expr), self.span_from(start_span)))
         } else {
-            eprintln!("Error: Expected '=' for assignment or ';' after expression.");
-            return None;
+            Err(self.error_expected(
+                vec![Token::Equal, Token::Semicolon],
+                "Expected '=' for assignment or ';' after expression.",
+            ))
         }
     }
 
-    fn parse_expression(&mut self) -> Option<Expression> {
+    // Precedence, loosest first (the README has the same table):
+    //   =  +=  -=  *=  /=  %=     assignment (right-associative)
+    //   or
+    //   and
+    //   ==  !=  ~=
+    //   <  <=  >  >=
+    //   ..  ..=                   ranges (don't chain)
+    //   |
+    //   ~                         xor
+    //   &
+    //   <<  >>
+    //   +  -
+    //   *  /  %  div
+    //   -  ~  some  not           unary
+    //   ^                         exponent (right-associative)
+    //   calls, indexing, '.'
+    fn parse_expression(&mut self) -> ParseResult<Expression> {
         // Keeping old tests here for reference
         // self.parse_primary(); // Start with the simplest elements
         // self.parse_unary() // handles unary operators ('-' and '~')
         // self.parse_term() // handles binary operators ('+', '-', '*', '/')
         // self.parse_comparison() // handles comparison operators ('<', '>', '<=', '>=')
         // self.parse_equality() // handles equality operators ('==', '!=')
-        self.parse_logical_or() // handles logical operators ('&&', '||')
+        self.parse_assignment() // handles '=', '+=', '-=', '*=', '/=' and '%='
+    }
+
+    // Assignment is right-associative and binds loosest of all, so `a = b += 1` is `a = (b += 1)`.
+    fn parse_assignment(&mut self) -> ParseResult<Expression> {
+        let expr = self.parse_logical_or()?;
+
+        self.skip_comments();
+        let operator = match self.current_token() {
+            Token::Equal => None,
+            Token::PlusEqual => Some(Operator::Plus),
+            Token::MinusEqual => Some(Operator::Minus),
+            Token::StarEqual => Some(Operator::Multiply),
+            Token::SlashEqual => Some(Operator::Divide),
+            Token::PercentEqual => Some(Operator::Modulus),
+            _ => return Ok(expr),
+        };
+        self.advance();
+
+        let span = expr.span;
+        let target = match expr.kind {
+            ExpressionKind::Variable(name) => AssignTarget::Variable(name),
+            ExpressionKind::Index(object, index) => AssignTarget::Index { object: *object, index: *index },
+            ExpressionKind::Get(object, name) => AssignTarget::Field { object: *object, name },
+            _ => {
+                return Err(ParseError::new(
+                    ParseErrorKind::InvalidAssignmentTarget,
+                    "Invalid assignment target. Must be an identifier, an index like 'list[i]' or a field like 'map.key'.",
+                    span,
+                ))
+            }
+        };
+
+        self.skip_comments();
+        let value = self.parse_assignment()?;
+        let span = span.to(value.span);
+        Ok(Expression::new(
+            ExpressionKind::Assignment { target: Box::new(target), operator, value: Box::new(value) },
+            span,
+        ))
     }
 
     // Each function below is fed into the function below it
-    fn parse_primary(&mut self) -> Option<Expression> {
+    fn parse_primary(&mut self) -> ParseResult<Expression> {
         let current_token = self.current_token().clone();
+        let start_span = self.current_span();
 
         match current_token {
+            Token::Integer(value) => {
+                self.advance();
+                Ok(Expression::new(ExpressionKind::Literal(Literal::Int(value)), start_span))
+            }
+            Token::BigInteger(digits) => {
+                self.advance();
+                Ok(Expression::new(ExpressionKind::Literal(Literal::BigInt(digits)), start_span))
+            }
             Token::Number(value) => {
                 self.advance();   
-                Some(Expression::Literal(Literal::Number(value)))
+                Ok(Expression::new(ExpressionKind::Literal(Literal::Number(value)), start_span))
             }
             Token::StringLiteral(value) => {
                 self.advance();   
-                Some(Expression::Literal(Literal::String(value)))
+                Ok(Expression::new(ExpressionKind::Literal(Literal::String(value)), start_span))
+            }
+            Token::Template(parts) => {
+                self.advance();
+                let mut interpolated = Vec::with_capacity(parts.len());
+                for part in parts {
+                    interpolated.push(match part {
+                        TemplatePart::Literal(text) => InterpolationPart::Literal(text),
+                        TemplatePart::Expression(tokens) => {
+                            InterpolationPart::Expression(self.parse_interpolated_expression(tokens)?)
+                        }
+                    });
+                }
+                Ok(Expression::new(ExpressionKind::Interpolation(interpolated), start_span))
             }
             Token::True => {
                 self.advance();   
-                Some(Expression::Literal(Literal::Boolean(true)))
+                Ok(Expression::new(ExpressionKind::Literal(Literal::Boolean(true)), start_span))
             }
             Token::False => {
                 self.advance();   
-                Some(Expression::Literal(Literal::Boolean(false)))
+                Ok(Expression::new(ExpressionKind::Literal(Literal::Boolean(false)), start_span))
             }
             Token::Nil => {
                 self.advance();
-                Some(Expression::Literal(Literal::Nil))
+                Ok(Expression::new(ExpressionKind::Literal(Literal::Nil), start_span))
+            }
+            Token::LeftBracket => {
+                self.advance();
+                let mut elements = Vec::new();
+                while !self.check(&Token::RightBracket) {
+                    elements.push(self.parse_expression()?);
+                    if !self.match_token(Token::Comma) {
+                        break; // No comma, so this must be the last element
+                    }
+                }
+                self.consume(Token::RightBracket, "Expected ']' after list elements.")?;
+                Ok(Expression::new(ExpressionKind::List(elements), self.span_from(start_span)))
+            }
+            Token::LeftBrace => self.parse_map_literal(),
+            Token::Func => self.parse_function_expression(),
+            Token::Sym => {
+                self.advance();
+                let name = self.consume_identifier("Expected a name after 'sym'.")?;
+                Ok(Expression::new(ExpressionKind::Literal(Literal::Symbol(name)), self.span_from(start_span)))
             }
             Token::Identifier(name) => {
                 self.advance();   
-                Some(Expression::Variable(name))
+                Ok(Expression::new(ExpressionKind::Variable(name), start_span))
             }
             Token::LeftParen => {
                 self.advance(); 
                 let expr = self.parse_expression()?; 
 
-                if self.match_token(Token::RightParen) {
-                     // Return the inner expression, wrapped in Grouping AST node
-                    Some(Expression::Grouping(Box::new(expr)))
-                } else {
-                    eprintln!("Error: Expected ')' after expression in parentheses.");
-                    None // Error: Missing closing parenthesis
-                }
+                self.consume(Token::RightParen, "Expected ')' after expression in parentheses.")?;
+                // Return the inner expression, wrapped in Grouping AST node
+                Ok(Expression::new(ExpressionKind::Grouping(Box::new(expr)), self.span_from(start_span)))
             }
             // Add cases for other primary expressions like 'nil' if you add it
-            _ => {
-                // Error: Unexpected token when expecting a primary expression
-                eprintln!("Error: Unexpected token '{:?}' while parsing primary expression.", self.current_token());
-                None
+            _ => Err(self.error_at_current(ParseErrorKind::UnexpectedToken, "Expected an expression.")),
+        }
+    }
+
+    // Each `${...}` in a template holds exactly one expression, lexed ahead of time.
+    // The EOF ending its tokens stands for the closing '}', so errors say so.
+    fn parse_interpolated_expression(&mut self, tokens: Vec<SpannedToken>) -> ParseResult<Expression> {
+        let mut parser = Parser::new(tokens);
+        let result = if parser.is_at_end() {
+            Err(parser.error_at_current(
+                ParseErrorKind::UnexpectedToken,
+                "Expected an expression inside '${}'.",
+            ))
+        } else {
+            parser.parse_expression().and_then(|expr| {
+                if parser.is_at_end() {
+                    Ok(expr)
+                } else {
+                    Err(parser.error_expected(
+                        vec![Token::RightBrace],
+                        "Expected '}' after interpolated expression.",
+                    ))
+                }
+            })
+        };
+
+        let at_closing_brace = |error: ParseError| match error.kind {
+            ParseErrorKind::UnexpectedEof => ParseError {
+                kind: ParseErrorKind::UnexpectedToken,
+                found: Some(Token::RightBrace),
+                ..error
+            },
+            _ => error,
+        };
+        // Errors recovered from inside it, e.g. in the body of a `func` expression,
+        // belong to the program being parsed like any other.
+        self.errors.extend(parser.errors.drain(..).map(at_closing_brace));
+        result.map_err(at_closing_brace)
+    }
+
+    // `{ "a": 1, b: 2 }`. A bare identifier key is shorthand for a string key, so `b`
+    // above means "b"; any other key is an expression. Only reached in expression
+    // position: a '{' that starts a statement is always a block.
+    fn parse_map_literal(&mut self) -> ParseResult<Expression> {
+        let start_span = self.current_span();
+        self.consume(Token::LeftBrace, "Expected '{'.")?;
+
+        let mut entries = Vec::new();
+        while !self.check(&Token::RightBrace) {
+            let key = match self.current_token().clone() {
+                Token::Identifier(name) if self.peek() == &Token::Colon => {
+                    let key_span = self.current_span();
+                    self.advance();
+                    Expression::new(ExpressionKind::Literal(Literal::String(name)), key_span)
+                }
+                _ => self.parse_expression()?,
+            };
+            self.consume(Token::Colon, "Expected ':' after map key.")?;
+            let value = self.parse_expression()?;
+            entries.push((key, value));
+
+            if !self.match_token(Token::Comma) {
+                break; // No comma, so this must be the last entry
             }
         }
+
+        self.consume(Token::RightBrace, "Expected '}' after map entries.")?;
+        Ok(Expression::new(ExpressionKind::Map(entries), self.span_from(start_span)))
     }
 
-    fn parse_unary(&mut self) -> Option<Expression> {
+    fn parse_unary(&mut self) -> ParseResult<Expression> {
         let operator_token_snapshot = self.current_token().clone();
+        let start_span = self.current_span();
 
         match operator_token_snapshot {
             Token::Minus | Token::Tilde | Token::Some | Token::Not => {
@@ -125,15 +272,30 @@ impl Parser {
                 };
                 
                 let right_operand = self.parse_unary()?;
-                Some(Expression::Unary(ast_operator, Box::new(right_operand)))
+                Ok(Expression::new(
+                    ExpressionKind::Unary(ast_operator, Box::new(right_operand)),
+                    self.span_from(start_span),
+                ))
             }
             _ => {
-                self.parse_call_expression()
+                self.parse_power()
             }
         }
     }
 
-    fn parse_term(&mut self) -> Option<Expression> {
+    // `^` binds tighter than the unary operators and groups to the right, so `-2 ^ 2` is
+    // `-(2 ^ 2)` and `2 ^ 3 ^ 2` is `2 ^ (3 ^ 2)`. The exponent may itself be negated: `2 ^ -1`.
+    fn parse_power(&mut self) -> ParseResult<Expression> {
+        let base = self.parse_call_expression()?;
+
+        if self.match_token(Token::Caret) {
+            let exponent = self.parse_unary()?;
+            return Ok(Self::make_binary(base, Operator::Power, exponent));
+        }
+        Ok(base)
+    }
+
+    fn parse_term(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_factor()?;
 
         while matches!(self.current_token(), Token::Plus | Token::Minus) {
@@ -147,15 +309,15 @@ impl Parser {
             };
 
             let right_operand = self.parse_factor()?;
-            expr = Expression::Binary(Box::new(expr), ast_operator, Box::new(right_operand));
+            expr = Self::make_binary(expr, ast_operator, right_operand);
         }
-        Some(expr)
+        Ok(expr)
     }
 
-    fn parse_factor(&mut self) -> Option<Expression> {
+    fn parse_factor(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_unary()?;
 
-        while matches!(self.current_token(), Token::Star | Token::Slash | Token::Percent) {
+        while matches!(self.current_token(), Token::Star | Token::Slash | Token::Percent | Token::Div) {
             let operator_token = self.current_token().clone();
             self.advance();
 
@@ -163,18 +325,89 @@ impl Parser {
                 Token::Star => Operator::Multiply,
                 Token::Slash => Operator::Divide,
                 Token::Percent => Operator::Modulus,
+                Token::Div => Operator::IntegerDivide,
                 _ => unreachable!("Lexer should not produce other tokens here if first match is star/slash. Checked by matches! macro."),
             };
 
             let right_operand = self.parse_unary()?;
-            expr = Expression::Binary(Box::new(expr), ast_operator, Box::new(right_operand));
+            expr = Self::make_binary(expr, ast_operator, right_operand);
         }
-        Some(expr)
+        Ok(expr)
     }
 
-    fn parse_comparison(&mut self) -> Option<Expression> {
+    // Bitwise operators sit between arithmetic and comparison, as in Lua, so
+    // `x & 1 == 0` is `(x & 1) == 0` and `1 << n - 1` is `1 << (n - 1)`.
+    fn parse_shift(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_term()?;
 
+        while matches!(self.current_token(), Token::LessLess | Token::GreaterGreater) {
+            let ast_operator = if self.match_token(Token::LessLess) {
+                Operator::ShiftLeft
+            } else {
+                self.advance();
+                Operator::ShiftRight
+            };
+
+            let right_operand = self.parse_term()?;
+            expr = Self::make_binary(expr, ast_operator, right_operand);
+        }
+        Ok(expr)
+    }
+
+    fn parse_bit_and(&mut self) -> ParseResult<Expression> {
+        let mut expr = self.parse_shift()?;
+
+        while self.match_token(Token::Ampersand) {
+            let right_operand = self.parse_shift()?;
+            expr = Self::make_binary(expr, Operator::BitAnd, right_operand);
+        }
+        Ok(expr)
+    }
+
+    // `~` between two operands is xor; in front of one it is the "almost" operator.
+    fn parse_bit_xor(&mut self) -> ParseResult<Expression> {
+        let mut expr = self.parse_bit_and()?;
+
+        while self.match_token(Token::Tilde) {
+            let right_operand = self.parse_bit_and()?;
+            expr = Self::make_binary(expr, Operator::BitXor, right_operand);
+        }
+        Ok(expr)
+    }
+
+    fn parse_bit_or(&mut self) -> ParseResult<Expression> {
+        let mut expr = self.parse_bit_xor()?;
+
+        while self.match_token(Token::Pipe) {
+            let right_operand = self.parse_bit_xor()?;
+            expr = Self::make_binary(expr, Operator::BitOr, right_operand);
+        }
+        Ok(expr)
+    }
+
+    // `start..end` and `start..=end` bind looser than arithmetic, so `0..n - 1` is `0..(n - 1)`.
+    // They don't chain: `0..1..2` is an error.
+    fn parse_range(&mut self) -> ParseResult<Expression> {
+        let start = self.parse_bit_or()?;
+
+        let inclusive = match self.current_token() {
+            Token::DotDot => false,
+            Token::DotDotEqual => true,
+            _ => return Ok(start),
+        };
+        self.advance();
+
+        let end = self.parse_bit_or()?;
+        let span = start.span.to(end.span);
+        Ok(Expression::new(
+            ExpressionKind::Range { start: Box::new(start), end: Box::new(end), inclusive },
+            span,
+        ))
+    }
+
+    fn parse_comparison(&mut self) -> ParseResult<Expression> {
+        let mut expr = self.parse_range()?;
+
         while matches!(
             self.current_token(),
             Token::Greater | Token::GreaterEqual | Token::Less | Token::LessEqual
@@ -190,121 +423,139 @@ impl Parser {
                 _ => unreachable!("Checked by matches! macro"),
             };
 
-            let right_operand = self.parse_term()?; 
-            expr = Expression::Binary(Box::new(expr), ast_operator, Box::new(right_operand));
+            let right_operand = self.parse_range()?; 
+            expr = Self::make_binary(expr, ast_operator, right_operand);
         }
-        Some(expr)
+        Ok(expr)
     }
 
-    fn parse_equality(&mut self) -> Option<Expression> {
+    fn parse_equality(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_comparison()?;
 
-        while matches!(self.current_token(), Token::EqualEqual | Token::BangEqual) {
+        while matches!(self.current_token(), Token::EqualEqual | Token::BangEqual | Token::TildeEqual) {
             let operator_token = self.current_token().clone();
             self.advance(); 
 
             let ast_operator = match operator_token {
                 Token::EqualEqual => Operator::EqualEqual,
                 Token::BangEqual => Operator::NotEqual,
+                Token::TildeEqual => Operator::AlmostEqual,
                 _ => unreachable!("Checked by matches! macro"),
             };
 
             let right_operand = self.parse_comparison()?; 
-            expr = Expression::Binary(Box::new(expr), ast_operator, Box::new(right_operand));
+            expr = Self::make_binary(expr, ast_operator, right_operand);
         }
-        Some(expr)
+        Ok(expr)
     }
 
-    fn parse_logical_or(&mut self) -> Option<Expression> {
+    fn parse_logical_or(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_logical_and()?;
 
         while matches!(self.current_token(), Token::Or) {
-            let operator_token = self.current_token().clone();
             self.advance();
 
             let ast_operator = Operator::Or;
 
             let right_operand = self.parse_logical_and()?;
-            expr = Expression::Binary(Box::new(expr), ast_operator, Box::new(right_operand));
+            expr = Self::make_binary(expr, ast_operator, right_operand);
         }
-        Some(expr)
+        Ok(expr)
     }
 
-    fn parse_logical_and(&mut self) -> Option<Expression> {
+    fn parse_logical_and(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_equality()?;
 
         while matches!(self.current_token(), Token::And) {
-            let operator_token = self.current_token().clone();
             self.advance();
 
             let ast_operator = Operator::And;
 
             let right_operand = self.parse_equality()?;
-            expr = Expression::Binary(Box::new(expr), ast_operator, Box::new(right_operand));
+            expr = Self::make_binary(expr, ast_operator, right_operand);
         }
-        Some(expr)
+        Ok(expr)
+    }
+
+    // Builds a binary node whose span covers both operands.
+    fn make_binary(left: Expression, operator: Operator, right: Expression) -> Expression {
+        let span = left.span.to(right.span);
+        Expression::new(ExpressionKind::Binary(Box::new(left), operator, Box::new(right)), span)
     }
 
-    fn parse_call_expression(&mut self) -> Option<Expression> {
+    fn parse_call_expression(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_primary()?;
 
         loop {
             if self.check(&Token::LeftParen) {
                 expr = self.finish_call(expr)?;
+            } else if self.match_token(Token::LeftBracket) {
+                let index = self.parse_expression()?;
+                self.consume(Token::RightBracket, "Expected ']' after index.")?;
+                let span = expr.span.to(self.previous_span());
+                expr = Expression::new(ExpressionKind::Index(Box::new(expr), Box::new(index)), span);
+            } else if self.match_token(Token::Dot) {
+                let member = self.consume_identifier("Expected member name after '.'.")?;
+                let span = expr.span.to(self.previous_span());
+                expr = Expression::new(ExpressionKind::Get(Box::new(expr), member), span);
             } else {
                 break;
             }
         }
-        Some(expr)
+        Ok(expr)
     }
 
-    fn finish_call(&mut self, callee: Expression) -> Option<Expression> {
+    fn finish_call(&mut self, callee: Expression) -> ParseResult<Expression> {
         self.advance();
 
         let arguments = self.parse_arguments()?;
 
-        if !self.match_token(Token::RightParen) {
-            eprintln!("Error: Expected ')' after arguments in function call.");
-            return None;
-        }
-        Some(Expression::FunctionCall(Box::new(callee), arguments))
+        self.consume(Token::RightParen, "Expected ')' after arguments in function call.")?;
+        let span = callee.span.to(self.previous_span());
+        Ok(Expression::new(ExpressionKind::FunctionCall(Box::new(callee), arguments), span))
     }
 
-    fn parse_arguments(&mut self) -> Option<Vec<Expression>> {
+    fn parse_arguments(&mut self) -> ParseResult<Vec<Expression>> {
         let mut arguments = Vec::new();
 
         if self.check(&Token::RightParen) {
-            return Some(arguments);
+            return Ok(arguments);
         }
 
-        match self.parse_expression() {
-            Some(arg) => arguments.push(arg),
-            None => return None,
-        }
+        arguments.push(self.parse_expression()?);
 
         while self.match_token(Token::Comma) {
-            match self.parse_expression() {
-                Some(arg) => arguments.push(arg),
-                None => return None,
-            }
+            arguments.push(self.parse_expression()?);
         }
-        Some(arguments)
+        Ok(arguments)
     }
 
-    pub fn parse_statement(&mut self) -> Option<Statement> {
+    pub fn parse_statement(&mut self) -> ParseResult<Statement> {
         self.skip_comments();
-        if self.is_at_end() { return None; }
+        if self.is_at_end() {
+            return Err(self.error_at_current(ParseErrorKind::UnexpectedEof, "Expected a statement."));
+        }
         
         if self.check(&Token::Return) {
             self.parse_return_statement()
+        } else if self.check(&Token::Break) || self.check(&Token::Continue) {
+            self.parse_loop_control_statement()
         } else if self.check(&Token::For) {
             self.parse_for_statement()
-        } else if self.check(&Token::Func) {
+        } else if self.check(&Token::Func) && matches!(self.peek(), Token::Identifier(_)) {
             self.parse_function_declaration()
         } else if self.check(&Token::Print) {
             self.parse_print_statement()
         } else if self.check(&Token::Let) {
             self.parse_variable_declaration()
+        } else if self.check(&Token::Const) {
+            self.parse_const_declaration()
+        } else if self.check(&Token::Module) {
+            self.parse_module_declaration()
+        } else if self.check(&Token::Pub) {
+            self.parse_public_declaration()
+        } else if self.check(&Token::Import) {
+            self.parse_import_statement()
         } else if self.check(&Token::LeftBrace) {
             self.parse_block_statement()
         } else if self.check(&Token::If) {
@@ -316,121 +567,114 @@ impl Parser {
         }
     }
 
-        fn parse_function_declaration(&mut self) -> Option<Statement> {
-        if !self.match_token(Token::Func) { 
-            eprintln!("Internal parser error: Expected 'func' token in parse_function_declaration.");
-            return None;
-        }
+    fn parse_function_declaration(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::Func, "Expected 'func'.")?;
 
-        let name = match self.peek_and_advance() { 
-            Some(Token::Identifier(name_str)) => name_str,
-            _ => {
-                eprintln!("Error: Expected function name (identifier) after 'func'.");
-                return None;
-            }
-        };
+        let name = self.consume_identifier("Expected function name (identifier) after 'func'.")?;
 
-        if !self.match_token(Token::LeftParen) {
-            eprintln!("Error: Expected '(' after function name '{}'.", name);
-            return None;
-        }
+        self.consume(Token::LeftParen, &format!("Expected '(' after function name '{}'.", name))?;
 
         let params = self.parse_parameters()?; 
 
-        if !self.match_token(Token::RightParen) {
-            eprintln!("Error: Expected ')' after function parameters for function '{}'.", name);
-            return None;
+        self.consume(Token::RightParen, &format!("Expected ')' after function parameters for function '{}'.", name))?;
+
+        let is_expression_body = self.check(&Token::FatArrow);
+        let body = self.parse_function_body(&format!("Expected '{{' or '=>' for function body of '{}'.", name))?;
+        if is_expression_body {
+            self.skip_comments();
+            self.consume(Token::Semicolon, &format!("Expected ';' after expression body of function '{}'.", name))?;
+        }
+
+        Ok(Statement::new(
+            StatementKind::FunctionDeclaration { name, params, body },
+            self.span_from(start_span),
+        ))
+    }
+
+    // `func (params) { ... }` or `func (params) => expression`, as a value.
+    fn parse_function_expression(&mut self) -> ParseResult<Expression> {
+        let start_span = self.current_span();
+        self.consume(Token::Func, "Expected 'func'.")?;
+        self.consume(Token::LeftParen, "Expected '(' after 'func' in function expression.")?;
+        let params = self.parse_parameters()?;
+        self.consume(Token::RightParen, "Expected ')' after function parameters.")?;
+
+        let body = self.parse_function_body("Expected '{' or '=>' for function body.")?;
+        Ok(Expression::new(ExpressionKind::Function { params, body }, self.span_from(start_span)))
+    }
+
+    // A block body, or `=> expression`, which is shorthand for `{ return expression; }`.
+    fn parse_function_body(&mut self, message: &str) -> ParseResult<Vec<Statement>> {
+        if self.match_token(Token::FatArrow) {
+            self.skip_comments();
+            let value = self.parse_expression()?;
+            let span = value.span;
+            return Ok(vec![Statement::new(StatementKind::ReturnStatement(Some(value)), span)]);
         }
 
         if !self.check(&Token::LeftBrace) {
-            eprintln!("Error: Expected '{{' for function body of '{}'.", name);
-            return None;
+            return Err(self.error_expected(vec![Token::LeftBrace, Token::FatArrow], message));
         }
 
-        let body_statement = self.parse_statement()?;
+        // A loop around the function doesn't make `break` valid inside it.
+        let enclosing_loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let body_statement = self.parse_block_statement();
+        self.loop_depth = enclosing_loop_depth;
 
-        match body_statement {
-            Statement::Block(body_statements) => {
-                Some(Statement::FunctionDeclaration { name, params, body: body_statements })
-            }
-            _ => {
-                eprintln!("Error: Function body must be a block statement for function '{}'.", name);
-                None
-            }
+        match body_statement?.kind {
+            StatementKind::Block(body_statements) => Ok(body_statements),
+            _ => unreachable!("parse_block_statement always produces a block"),
         }
     }
 
-    fn parse_parameters(&mut self) -> Option<Vec<String>> {
-                let mut parameters = Vec::new();
+    fn parse_parameters(&mut self) -> ParseResult<Vec<String>> {
+        let mut parameters = Vec::new();
 
         if self.check(&Token::RightParen) {
-            return Some(parameters);
+            return Ok(parameters);
         }
 
-        match self.peek_and_advance() {
-            Some(Token::Identifier(param_name)) => parameters.push(param_name),
-            _ => {
-                eprintln!("Error: Expected parameter name (identifier) in function parameter list.");
-                return None;
-            }
-        }
+        parameters.push(self.consume_identifier("Expected parameter name (identifier) in function parameter list.")?);
 
         while self.match_token(Token::Comma) {
-            match self.peek_and_advance() {
-                Some(Token::Identifier(param_name)) => parameters.push(param_name),
-                _ => {
-                    eprintln!("Error: Expected parameter name (identifier) after comma in function parameter list.");
-                    return None;
-                }
-            }
+            parameters.push(self.consume_identifier(
+                "Expected parameter name (identifier) after comma in function parameter list.",
+            )?);
         }
-        Some(parameters)
+        Ok(parameters)
     }
 
-    fn parse_block_statement(&mut self) -> Option<Statement> {
-        if !self.match_token(Token::LeftBrace) { 
-            eprintln!("Error: Expected '{{' to start a block."); 
-            return None;
-        }
+    fn parse_block_statement(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::LeftBrace, "Expected '{' to start a block.")?;
 
         let mut statements: Vec<Statement> = Vec::new();
 
+        self.skip_comments();
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
-            match self.parse_statement() {
-                Some(stmt) => statements.push(stmt),
-                None => {
-                    return None;
-                }
+            // A bad statement inside the block shouldn't throw away the rest of it.
+            if let Some(stmt) = self.parse_statement_or_recover() {
+                statements.push(stmt);
             }
+            self.skip_comments();
         }
 
-        if !self.match_token(Token::RightBrace) { 
-            eprintln!("Error: Expected '}}' to close a block.");
-            return None;
-        }
+        self.consume(Token::RightBrace, "Expected '}' to close a block.")?;
 
-        Some(Statement::Block(statements))
+        Ok(Statement::new(StatementKind::Block(statements), self.span_from(start_span)))
     }
 
-        fn parse_if_statement(&mut self) -> Option<Statement> {
-        if !self.match_token(Token::If) {
-            eprintln!("Internal parser error: Expected 'if' token in parse_if_statement.");
-            return None;
-        }
+    fn parse_if_statement(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::If, "Expected 'if'.")?;
 
-        if !self.match_token(Token::LeftParen) {
-            eprintln!("Error: Expected '(' after 'if'.");
-            return None;
-        }
+        self.consume(Token::LeftParen, "Expected '(' after 'if'.")?;
         let condition = self.parse_expression()?;
-        if !self.match_token(Token::RightParen) {
-            eprintln!("Error: Expected ')' after if condition.");
-            return None;
-        }
+        self.consume(Token::RightParen, "Expected ')' after if condition.")?;
 
         if !self.check(&Token::LeftBrace) {
-            eprintln!("Error: Expected '{{' for if statement body.");
-            return None;
+            return Err(self.error_expected(vec![Token::LeftBrace], "Expected '{' for if statement body."));
         }
         let then_branch = Box::new(self.parse_statement()?);
 
@@ -439,152 +683,314 @@ impl Parser {
             if self.check(&Token::LeftBrace) {
                 else_branch_opt = Some(Box::new(self.parse_statement()?));
             } else {
-                eprintln!("Error: Expected '{{' or 'if' after 'else'.");
-                return None;
+                return Err(self.error_expected(vec![Token::LeftBrace, Token::If], "Expected '{' or 'if' after 'else'."));
             }
         }
 
-        Some(Statement::IfStatement(condition, then_branch, else_branch_opt))
+        Ok(Statement::new(
+            StatementKind::IfStatement(condition, then_branch, else_branch_opt),
+            self.span_from(start_span),
+        ))
     }
 
-        fn parse_while_statement(&mut self) -> Option<Statement> {
-        if !self.match_token(Token::While) { 
-            eprintln!("Internal parser error: Expected 'while' token in parse_while_statement.");
-            return None;
-        }
+    fn parse_while_statement(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::While, "Expected 'while'.")?;
 
-        if !self.match_token(Token::LeftParen) {
-            eprintln!("Error: Expected '(' after 'while'.");
-            return None;
-        }
+        self.consume(Token::LeftParen, "Expected '(' after 'while'.")?;
         let condition = self.parse_expression()?;
-        if !self.match_token(Token::RightParen) {
-            eprintln!("Error: Expected ')' after while condition.");
-            return None;
-        }
+        self.consume(Token::RightParen, "Expected ')' after while condition.")?;
 
         if !self.check(&Token::LeftBrace) {
-            eprintln!("Error: Expected '{{' for while statement body.");
-            return None;
+            return Err(self.error_expected(vec![Token::LeftBrace], "Expected '{' for while statement body."));
         }
-        let body = Box::new(self.parse_statement()?);
+        let body = Box::new(self.parse_loop_body()?);
 
-        Some(Statement::WhileStatement(condition, body))
+        Ok(Statement::new(StatementKind::WhileStatement(condition, body), self.span_from(start_span)))
     }
 
-    fn parse_for_statement(&mut self) -> Option<Statement> {
-        if !self.match_token(Token::For) { return None; } // Consume 'for'
+    fn parse_for_statement(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::For, "Expected 'for'.")?;
 
-        if !self.match_token(Token::LeftParen) {
-            eprintln!("Error: Expected '(' after 'for'.");
-            return None;
-        }
+        self.consume(Token::LeftParen, "Expected '(' after 'for'.")?;
 
         self.skip_comments();
+        if matches!(self.current_token(), Token::Identifier(_)) && matches!(self.peek(), Token::In | Token::Comma) {
+            return self.finish_for_in_statement(start_span);
+        }
+
         let initializer: Box<Statement>;
+        let initializer_span = self.current_span();
         if self.check(&Token::Let) {
             self.advance(); 
-            let var_name = match self.peek_and_advance() {
-                Some(Token::Identifier(name_str)) => name_str,
-                _ => {
-                    eprintln!("Error: Expected variable name after 'let' in for-loop initializer.");
-                    return None;
-                }
-            };
+            let var_name = self.consume_identifier("Expected variable name after 'let' in for-loop initializer.")?;
             let var_initializer_expr: Option<Expression> = if self.match_token(Token::Equal) {
                 self.skip_comments(); 
-                self.parse_expression()
+                Some(self.parse_expression()?)
             } else {
                 None
             };
-            initializer = Box::new(Statement::VariableDeclaration(var_name, var_initializer_expr));
+            initializer = Box::new(Statement::new(
+                StatementKind::VariableDeclaration(var_name, var_initializer_expr),
+                self.span_from(initializer_span),
+            ));
             self.skip_comments(); 
-            if !self.match_token(Token::Semicolon) {
-                eprintln!("Error: Expected ';' after 'let' declaration in for-loop initializer.");
-                return None;
-            }
+            self.consume(Token::Semicolon, "Expected ';' after 'let' declaration in for-loop initializer.")?;
         } else if self.check(&Token::Semicolon) { // Check for ';' for empty initializer
             self.advance(); 
-            initializer = Box::new(Statement::ExpressionStatement(Expression::Literal(Literal::Nil)));
+            initializer = Box::new(Self::nil_placeholder(initializer_span));
         } else { 
             let init_expr = self.parse_expression()?;
-            initializer = Box::new(Statement::ExpressionStatement(init_expr));
+            let init_span = init_expr.span;
+            initializer = Box::new(Statement::new(StatementKind::ExpressionStatement(init_expr), init_span));
             self.skip_comments(); 
-            if !self.match_token(Token::Semicolon) {
-                eprintln!("Error: Expected ';' after for-loop initializer expression.");
-                return None;
-            }
+            self.consume(Token::Semicolon, "Expected ';' after for-loop initializer expression.")?;
         }
 
         self.skip_comments(); 
         let condition: Expression;
         if self.check(&Token::Semicolon) { 
+            let condition_span = self.current_span();
             self.advance(); 
-            condition = Expression::Literal(Literal::Boolean(true)); 
+            condition = Expression::new(ExpressionKind::Literal(Literal::Boolean(true)), condition_span); 
         } else {
             condition = self.parse_expression()?;
             self.skip_comments(); 
-            if !self.match_token(Token::Semicolon) {
-                eprintln!("Error: Expected ';' after for-loop condition.");
-                return None;
-            }
+            self.consume(Token::Semicolon, "Expected ';' after for-loop condition.")?;
         }
 
         self.skip_comments(); 
         let increment: Box<Statement> = if self.check(&Token::RightParen) {
-            Box::new(Statement::ExpressionStatement(Expression::Literal(Literal::Nil)))
+            Box::new(Self::nil_placeholder(self.current_span()))
         } else {
             let incr_expr = self.parse_expression()?;
-            Box::new(Statement::ExpressionStatement(incr_expr))
+            let incr_span = incr_expr.span;
+            Box::new(Statement::new(StatementKind::ExpressionStatement(incr_expr), incr_span))
         };
 
         self.skip_comments(); 
-        if !self.match_token(Token::RightParen) {
-            eprintln!("Error: Expected ')' after for-loop clauses.");
-            return None;
+        self.consume(Token::RightParen, "Expected ')' after for-loop clauses.")?;
+
+        self.skip_comments();
+        if !self.check(&Token::LeftBrace) {
+            return Err(self.error_expected(vec![Token::LeftBrace], "Expected '{' for for-loop body."));
+        }
+        let body = Box::new(self.parse_loop_body()?);
+
+        Ok(Statement::new(
+            StatementKind::ForStatement(initializer, condition, increment, body),
+            self.span_from(start_span),
+        ))
+    }
+
+    // The rest of `for (item in iterable) { ... }` or `for (key, value in iterable) { ... }`,
+    // after the opening '('.
+    fn finish_for_in_statement(&mut self, start_span: Span) -> ParseResult<Statement> {
+        let mut variables = vec![self.consume_identifier("Expected loop variable name.")?];
+        if self.match_token(Token::Comma) {
+            variables.push(self.consume_identifier("Expected second loop variable name after ','.")?);
         }
+        self.consume(Token::In, "Expected 'in' after loop variables.")?;
+
+        self.skip_comments();
+        let iterable = self.parse_expression()?;
+
+        self.skip_comments();
+        self.consume(Token::RightParen, "Expected ')' after for-in iterable.")?;
 
         self.skip_comments();
         if !self.check(&Token::LeftBrace) {
-            eprintln!("Error: Expected '{{' for for-loop body.");
-            return None;
+            return Err(self.error_expected(vec![Token::LeftBrace], "Expected '{' for for-loop body."));
         }
-        let body = Box::new(self.parse_statement()?);
+        let body = Box::new(self.parse_loop_body()?);
+
+        Ok(Statement::new(
+            StatementKind::ForInStatement { variables, iterable, body },
+            self.span_from(start_span),
+        ))
+    }
+
+    // Parses the body of a `while` or `for` loop, where `break` and `continue` are allowed.
+    fn parse_loop_body(&mut self) -> ParseResult<Statement> {
+        self.loop_depth += 1;
+        let body = self.parse_statement();
+        self.loop_depth -= 1;
+        body
+    }
 
-        Some(Statement::ForStatement(initializer, condition, increment, body))
+    // Empty for-loop clauses are filled with a `nil;` statement so the AST always has one there.
+    fn nil_placeholder(span: Span) -> Statement {
+        Statement::new(
+            StatementKind::ExpressionStatement(Expression::new(ExpressionKind::Literal(Literal::Nil), span)),
+            span,
+        )
     }
 
-    fn parse_print_statement(&mut self) -> Option<Statement> {
-        if !self.match_token(Token::Print) { return None; } 
+    fn parse_print_statement(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::Print, "Expected 'print'.")?;
         let value = self.parse_expression()?;
-        if !self.match_token(Token::Semicolon) {
-            eprintln!("Error: Expected ';' after print value.");
-            None
+        self.consume(Token::Semicolon, "Expected ';' after print value.")?;
+        Ok(Statement::new(StatementKind::PrintStatement(value), self.span_from(start_span)))
+    }
+
+    fn parse_variable_declaration(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::Let, "Expected 'let'.")?;
+        let name = self.consume_identifier("Expected variable name after 'let'.")?;
+
+        let initializer = if self.match_token(Token::Equal) {
+            Some(self.parse_expression()?)
         } else {
-            Some(Statement::PrintStatement(value))
+            None
+        };
+
+        self.consume(Token::Semicolon, "Expected ';' after variable declaration.")?;
+        Ok(Statement::new(
+            StatementKind::VariableDeclaration(name, initializer),
+            self.span_from(start_span),
+        ))
+    }
+
+    fn parse_const_declaration(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::Const, "Expected 'const'.")?;
+        let name = self.consume_identifier("Expected constant name after 'const'.")?;
+
+        self.consume(Token::Equal, "Expected '=' after constant name; constants need a value.")?;
+        let value = self.parse_expression()?;
+
+        self.consume(Token::Semicolon, "Expected ';' after constant declaration.")?;
+        Ok(Statement::new(
+            StatementKind::ConstDeclaration(name, value),
+            self.span_from(start_span),
+        ))
+    }
+
+    fn parse_module_declaration(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::Module, "Expected 'module'.")?;
+        let name = self.consume_identifier("Expected module name after 'module'.")?;
+
+        if !self.check(&Token::LeftBrace) {
+            return Err(self.error_expected(vec![Token::LeftBrace], "Expected '{' before module body."));
+        }
+        let enclosing_loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let body_statement = self.parse_block_statement();
+        self.loop_depth = enclosing_loop_depth;
+
+        match body_statement?.kind {
+            StatementKind::Block(body) => Ok(Statement::new(
+                StatementKind::ModuleDeclaration { name, body },
+                self.span_from(start_span),
+            )),
+            _ => unreachable!("parse_block_statement always returns a block"),
         }
     }
 
-    fn parse_variable_declaration(&mut self) -> Option<Statement> {
-        if !self.match_token(Token::Let) { return None; } 
-        let token_option = self.peek_and_advance(); 
+    fn parse_public_declaration(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::Pub, "Expected 'pub'.")?;
 
-        if let Some(Token::Identifier(name)) = token_option {
-            let initializer = if self.match_token(Token::Equal) {
-                self.parse_expression()
-            } else {
-                None
-            };
+        let declaration = match self.current_token() {
+            Token::Let => self.parse_variable_declaration()?,
+            Token::Const => self.parse_const_declaration()?,
+            Token::Func => self.parse_function_declaration()?,
+            Token::Module => self.parse_module_declaration()?,
+            _ => {
+                return Err(self.error_expected(
+                    vec![Token::Let, Token::Const, Token::Func, Token::Module],
+                    "Expected a declaration after 'pub'.",
+                ))
+            }
+        };
+        Ok(Statement::new(
+            StatementKind::Public(Box::new(declaration)),
+            self.span_from(start_span),
+        ))
+    }
 
-            if !self.match_token(Token::Semicolon) {
-                eprintln!("Error: Expected ';' after variable declaration.");
-                None
-            } else {
-                Some(Statement::VariableDeclaration(name, initializer))
+    // `import "dir/name"` or `import name`. Either way the module is bound to `name`.
+    fn parse_import_statement(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::Import, "Expected 'import'.")?;
+
+        let path_span = self.current_span();
+        let path = match self.current_token().clone() {
+            Token::StringLiteral(path) => path,
+            Token::Identifier(name) => name,
+            _ => {
+                return Err(self.error_expected(
+                    vec![Token::StringLiteral(String::new()), Token::Identifier(String::new())],
+                    "Expected a module path or name after 'import'.",
+                ))
+            }
+        };
+        self.advance();
+
+        let name = Self::module_name_from_path(&path).ok_or_else(|| {
+            ParseError::new(
+                ParseErrorKind::UnexpectedToken,
+                format!("Can't import \"{}\": its file name isn't a valid identifier.", path),
+                path_span,
+            )
+        })?;
+
+        self.consume(Token::Semicolon, "Expected ';' after import.")?;
+        Ok(Statement::new(
+            StatementKind::Import { path, name },
+            self.span_from(start_span),
+        ))
+    }
+
+    // "lib/math.fddl" and "lib/math" both give "math".
+    fn module_name_from_path(path: &str) -> Option<String> {
+        let file_name = path.rsplit(['/', '\\']).next()?;
+        let stem = file_name.strip_suffix(".fddl").unwrap_or(file_name);
+
+        let mut chars = stem.chars();
+        let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_');
+        valid.then(|| stem.to_string())
+    }
+
+    // --- Error helpers ---
+
+    // An error pointing at the current token. Running out of input and lexer
+    // errors are reported as such, whatever `kind` the caller asked for.
+    fn error_at_current(&self, kind: ParseErrorKind, message: &str) -> ParseError {
+        let found = self.current_token().clone();
+        let span = self.current_span();
+        match &found {
+            Token::Error(lex_message) => {
+                ParseError::new(ParseErrorKind::LexError, lex_message.clone(), span).with_found(found)
             }
+            Token::EOF => ParseError::new(ParseErrorKind::UnexpectedEof, message, span).with_found(found),
+            _ => ParseError::new(kind, message, span).with_found(found),
+        }
+    }
+
+    fn error_expected(&self, expected: Vec<Token>, message: &str) -> ParseError {
+        self.error_at_current(ParseErrorKind::UnexpectedToken, message)
+            .with_expected(expected)
+    }
+
+    // Consumes `expected` or fails with `message`.
+    fn consume(&mut self, expected: Token, message: &str) -> ParseResult<()> {
+        if self.match_token(expected.clone()) {
+            Ok(())
         } else {
-            eprintln!("Error: Expected variable name after 'let'.");
-            None
+            Err(self.error_expected(vec![expected], message))
+        }
+    }
+
+    fn consume_identifier(&mut self, message: &str) -> ParseResult<String> {
+        if let Token::Identifier(name) = self.current_token().clone() {
+            self.advance();
+            Ok(name)
+        } else {
+            Err(self.error_expected(vec![Token::Identifier(String::new())], message))
         }
     }
 
@@ -596,11 +1002,31 @@ impl Parser {
     }
 
     fn current_token(&self) -> &Token {
-        self.tokens.get(self.current).unwrap_or(&Token::EOF)
+        self.tokens.get(self.current).map_or(&Token::EOF, |spanned| &spanned.token)
     }
 
     fn peek(&self) -> &Token {
-         self.tokens.get(self.current + 1).unwrap_or(&Token::EOF)
+         self.tokens.get(self.current + 1).map_or(&Token::EOF, |spanned| &spanned.token)
+    }
+
+    fn current_span(&self) -> Span {
+        self.tokens
+            .get(self.current)
+            .or_else(|| self.tokens.last())
+            .map_or(Span::default(), |spanned| spanned.span)
+    }
+
+    fn previous_span(&self) -> Span {
+        if self.current == 0 {
+            self.current_span()
+        } else {
+            self.tokens[self.current - 1].span
+        }
+    }
+
+    // Span from `start` up to the end of the most recently consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous_span())
     }
 
     fn advance(&mut self) -> &Token {
@@ -614,7 +1040,7 @@ impl Parser {
         if self.is_at_end() {
             None
         } else {
-            let token = self.tokens[self.current].clone();
+            let token = self.tokens[self.current].token.clone();
             self.current += 1;
             Some(token)
         }
@@ -680,71 +1106,159 @@ impl Parser {
 
     fn previous_token(&self) -> &Token {
         if self.current == 0 {
-             self.tokens.get(0).unwrap_or(&Token::EOF)
+             self.tokens.first().map_or(&Token::EOF, |spanned| &spanned.token)
         } else {
-            &self.tokens[self.current - 1]
+            &self.tokens[self.current - 1].token
         }
     }
 
     // 5-7-25
-    pub fn parse_program(&mut self) -> Vec<Statement> {
+    // Parses the whole token stream. On failure, returns every error that was collected.
+    pub fn parse_program(&mut self) -> Result<Vec<Statement>, Vec<ParseError>> {
+        let (statements, errors) = self.parse_program_with_recovery();
+
+        if errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Parses the whole token stream, skipping past bad statements instead of stopping.
+    // Returns whatever statements could be parsed alongside all the errors, so tooling
+    // can still work with a partial AST.
+    pub fn parse_program_with_recovery(&mut self) -> (Vec<Statement>, Vec<ParseError>) {
         let mut statements = Vec::new();
+
+        self.skip_comments();
         while !self.is_at_end_of_significant_tokens() {
-            match self.parse_statement() {
-                Some(stmt) => statements.push(stmt),
-                None => {
-                    eprintln!("Error: Could not parse statement.");
-                    break;
+            if let Some(stmt) = self.parse_statement_or_recover() {
+                statements.push(stmt);
+            }
+            self.skip_comments();
+        }
+
+        (statements, std::mem::take(&mut self.errors))
+    }
+
+    // Parses one statement. On error, records it and synchronizes so parsing can carry on.
+    fn parse_statement_or_recover(&mut self) -> Option<Statement> {
+        let statement_start = self.current;
+        match self.parse_statement() {
+            Ok(stmt) => Some(stmt),
+            Err(error) => {
+                self.errors.push(error);
+                self.synchronize();
+                // Always make progress, even if we failed on a token we'd synchronize to.
+                if self.current == statement_start && !self.is_at_end() {
+                    self.advance();
+                }
+                None
+            }
+        }
+    }
+
+    // Panic-mode recovery: skip tokens until we're at a plausible statement boundary.
+    // A ';' is consumed; a '}' or a statement keyword is left for the caller to handle.
+    // Any block opened while skipping is skipped as a whole, so its body and closing
+    // '}' don't turn into errors of their own.
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+        while !self.is_at_end() {
+            if depth > 0 {
+                match self.current_token() {
+                    Token::LeftBrace => depth += 1,
+                    Token::RightBrace => depth -= 1,
+                    _ => {}
+                }
+                self.advance();
+                if depth == 0 {
+                    return; // A skipped block ends the broken statement
+                }
+                continue;
+            }
+
+            match self.current_token() {
+                Token::Semicolon => {
+                    self.advance();
+                    return;
+                }
+                Token::LeftBrace => {
+                    depth += 1;
+                    self.advance();
+                }
+                Token::RightBrace
+                | Token::Let
+                | Token::Const
+                | Token::Module
+                | Token::Pub
+                | Token::Import
+                | Token::Func
+                | Token::If
+                | Token::While
+                | Token::For
+                | Token::Return
+                | Token::Break
+                | Token::Continue
+                | Token::Print => return,
+                _ => {
+                    self.advance();
                 }
             }
         }
-        statements
     }
 
     fn is_at_end_of_significant_tokens(&self) -> bool {
         if self.current >= self.tokens.len() { return true; }
-        matches!(self.tokens[self.current], Token::EOF)
+        matches!(self.tokens[self.current].token, Token::EOF)
     }
 
     pub fn is_at_end(&self) -> bool {
         if self.current >= self.tokens.len() { return true; }
-        matches!(self.tokens[self.current], Token::EOF)
+        matches!(self.tokens[self.current].token, Token::EOF)
     }
 
     //5-28/25
-    fn parse_return_statement(&mut self) -> Option<Statement> {
-        if !self.match_token(Token::Return) {
-            eprintln!("Internal parser error: Expected 'return' token.");
-            return None;
+    // `break;` or `continue;`. Outside a loop the error is recorded but parsing carries on,
+    // since the statement itself is well-formed.
+    fn parse_loop_control_statement(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        let (kind, keyword) = if self.match_token(Token::Break) {
+            (StatementKind::BreakStatement, "break")
+        } else {
+            self.consume(Token::Continue, "Expected 'break' or 'continue'.")?;
+            (StatementKind::ContinueStatement, "continue")
+        };
+
+        self.skip_comments();
+        self.consume(Token::Semicolon, &format!("Expected ';' after '{}'.", keyword))?;
+
+        let statement = Statement::new(kind, self.span_from(start_span));
+        if self.loop_depth == 0 {
+            self.errors.push(ParseError::new(
+                ParseErrorKind::OutsideLoop,
+                format!("'{}' can only be used inside a loop.", keyword),
+                statement.span,
+            ));
         }
+        Ok(statement)
+    }
+
+    fn parse_return_statement(&mut self) -> ParseResult<Statement> {
+        let start_span = self.current_span();
+        self.consume(Token::Return, "Expected 'return'.")?;
 
         self.skip_comments(); // Skip comments before the expression
 
         if self.check(&Token::Semicolon) {
             self.advance();
-            return Some(Statement::ReturnStatement(None));
-        }
-
-        if self.is_at_end() {
-            eprintln!("Error: Expected expression or ';' after 'return'.");
-            return None;
+            return Ok(Statement::new(StatementKind::ReturnStatement(None), self.span_from(start_span)));
         }
 
-        match self.parse_expression() {
-            Some(expr) => {
-                self.skip_comments(); // Skip comments after the expression
-                if !self.match_token(Token::Semicolon) {
-                    eprintln!("Error: Expected ';' after return expression.");
-                    return None;
-                }
-                Some(Statement::ReturnStatement(Some(expr)))
-            }
-            None => {
-                eprintln!("Error: Invalid expression after 'return'.");
-                None
-            }
-        }
+        let expr = self.parse_expression()?;
+        self.skip_comments(); // Skip comments after the expression
+        self.consume(Token::Semicolon, "Expected ';' after return expression.")?;
+        Ok(Statement::new(StatementKind::ReturnStatement(Some(expr)), self.span_from(start_span)))
     }
     
 }
-
@@ -0,0 +1,230 @@
+use crate::lexer::token::Span;
+use crate::parser::ast::{AssignTarget, Expression, ExpressionKind, InterpolationPart, Statement, StatementKind};
+use crate::parser::error::{ParseError, ParseErrorKind};
+use std::collections::HashMap;
+
+// Static checks that run on a parsed program before it is evaluated:
+// - assignments to constants whose declaration is lexically visible, and
+//   redeclarations of a constant in the scope that declared it. Names the
+//   resolver can't see (e.g. globals from an earlier REPL line) are left to the
+//   evaluator, which checks again at runtime.
+// - `pub` anywhere other than the top level of a file or module body.
+pub fn resolve(statements: &[Statement]) -> Result<(), Vec<ParseError>> {
+    let mut resolver = Resolver::new();
+    resolver.resolve_statements(statements);
+
+    if resolver.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(resolver.errors)
+    }
+}
+
+struct Resolver {
+    // Innermost scope last. A name maps to the span of its `const` declaration,
+    // or to None if it was declared some other way.
+    scopes: Vec<HashMap<String, Option<Span>>>,
+    errors: Vec<ParseError>,
+    at_module_level: bool, // Whether `pub` is allowed here
+}
+
+impl Resolver {
+    fn new() -> Self {
+        Resolver {
+            scopes: vec![HashMap::new()],
+            errors: Vec::new(),
+            at_module_level: true,
+        }
+    }
+
+    fn resolve_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+    }
+
+    // Mirrors the scopes the evaluator creates for each kind of statement.
+    fn resolve_statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::VariableDeclaration(name, initializer) => {
+                if let Some(initializer) = initializer {
+                    self.resolve_expression(initializer);
+                }
+                self.declare(name, statement.span, false);
+            }
+            StatementKind::ConstDeclaration(name, value) => {
+                self.resolve_expression(value);
+                self.declare(name, statement.span, true);
+            }
+            StatementKind::Block(statements) => {
+                self.in_scope(|resolver| resolver.resolve_statements(statements));
+            }
+            StatementKind::IfStatement(condition, then_branch, else_branch) => {
+                self.resolve_expression(condition);
+                self.resolve_statement(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.resolve_statement(else_branch);
+                }
+            }
+            StatementKind::WhileStatement(condition, body) => {
+                self.resolve_expression(condition);
+                self.resolve_statement(body);
+            }
+            StatementKind::ForStatement(initializer, condition, increment, body) => {
+                self.in_scope(|resolver| {
+                    resolver.resolve_statement(initializer);
+                    resolver.resolve_expression(condition);
+                    resolver.resolve_statement(body);
+                    resolver.resolve_statement(increment);
+                });
+            }
+            StatementKind::ForInStatement { variables, iterable, body } => {
+                self.resolve_expression(iterable);
+                self.in_scope(|resolver| {
+                    for variable in variables {
+                        resolver.declare(variable, statement.span, false);
+                    }
+                    resolver.resolve_statement(body);
+                });
+            }
+            StatementKind::FunctionDeclaration { name, params, body } => {
+                self.declare(name, statement.span, false);
+                self.resolve_function(params, body, statement.span);
+            }
+            StatementKind::ModuleDeclaration { name, body } => {
+                self.declare(name, statement.span, false);
+                self.in_scope(|resolver| {
+                    resolver.at_module_level = true;
+                    resolver.resolve_statements(body);
+                });
+            }
+            StatementKind::Public(declaration) => {
+                if !self.at_module_level {
+                    self.errors.push(ParseError::new(
+                        ParseErrorKind::MisplacedPub,
+                        "'pub' is only allowed at the top level of a file or module.",
+                        statement.span,
+                    ));
+                }
+                self.resolve_statement(declaration);
+            }
+            StatementKind::Import { name, .. } => self.declare(name, statement.span, false),
+            StatementKind::ExpressionStatement(expression) | StatementKind::PrintStatement(expression) => {
+                self.resolve_expression(expression)
+            }
+            StatementKind::ReturnStatement(value) => {
+                if let Some(value) = value {
+                    self.resolve_expression(value);
+                }
+            }
+            StatementKind::BreakStatement | StatementKind::ContinueStatement => {}
+        }
+    }
+
+    // Expressions only matter for the assignments and function bodies inside them,
+    // e.g. `i += 1` in a for-loop increment.
+    fn resolve_expression(&mut self, expression: &Expression) {
+        match &expression.kind {
+            ExpressionKind::Function { params, body } => self.resolve_function(params, body, expression.span),
+            ExpressionKind::Assignment { target, value, .. } => {
+                self.resolve_assignment(target, expression.span);
+                self.resolve_expression(value);
+            }
+            ExpressionKind::Binary(left, _, right)
+            | ExpressionKind::Index(left, right)
+            | ExpressionKind::Range { start: left, end: right, .. } => {
+                self.resolve_expression(left);
+                self.resolve_expression(right);
+            }
+            ExpressionKind::Unary(_, operand) | ExpressionKind::Grouping(operand) | ExpressionKind::Get(operand, _) => {
+                self.resolve_expression(operand)
+            }
+            ExpressionKind::FunctionCall(callee, arguments) => {
+                self.resolve_expression(callee);
+                for argument in arguments {
+                    self.resolve_expression(argument);
+                }
+            }
+            ExpressionKind::List(items) => {
+                for item in items {
+                    self.resolve_expression(item);
+                }
+            }
+            ExpressionKind::Map(entries) => {
+                for (key, value) in entries {
+                    self.resolve_expression(key);
+                    self.resolve_expression(value);
+                }
+            }
+            ExpressionKind::Interpolation(parts) => {
+                for part in parts {
+                    if let InterpolationPart::Expression(expression) = part {
+                        self.resolve_expression(expression);
+                    }
+                }
+            }
+            ExpressionKind::Literal(_) | ExpressionKind::Variable(_) => {}
+        }
+    }
+
+    fn resolve_function(&mut self, params: &[String], body: &[Statement], span: Span) {
+        self.in_scope(|resolver| {
+            for param in params {
+                resolver.declare(param, span, false);
+            }
+            resolver.resolve_statements(body);
+        });
+    }
+
+    fn resolve_assignment(&mut self, target: &AssignTarget, span: Span) {
+        match target {
+            AssignTarget::Variable(name) => self.check_assignment(name, span),
+            // Changing what's inside a constant list or map is fine; only rebinding the name isn't.
+            AssignTarget::Index { object, index } => {
+                self.resolve_expression(object);
+                self.resolve_expression(index);
+            }
+            AssignTarget::Field { object, .. } => self.resolve_expression(object),
+        }
+    }
+
+    fn in_scope(&mut self, body: impl FnOnce(&mut Self)) {
+        let was_at_module_level = std::mem::replace(&mut self.at_module_level, false);
+        self.scopes.push(HashMap::new());
+        body(self);
+        self.scopes.pop();
+        self.at_module_level = was_at_module_level;
+    }
+
+    // `span` is the declaration. Declaring a name again in the scope that made it
+    // `const` is an error, since it would quietly make the name assignable.
+    fn declare(&mut self, name: &str, span: Span, is_const: bool) {
+        let Some(scope) = self.scopes.last_mut() else { return };
+        if let Some(Some(declared_at)) = scope.get(name) {
+            self.errors.push(ParseError::new(
+                ParseErrorKind::AssignToConstant,
+                format!(
+                    "Cannot redeclare constant '{}' (declared at line {}, column {}).",
+                    name, declared_at.line, declared_at.column
+                ),
+                span,
+            ));
+            return;
+        }
+        scope.insert(name.to_string(), is_const.then_some(span));
+    }
+
+    fn check_assignment(&mut self, name: &str, span: Span) {
+        let declaration = self.scopes.iter().rev().find_map(|scope| scope.get(name));
+        if let Some(Some(declared_at)) = declaration {
+            self.errors.push(ParseError::new(
+                ParseErrorKind::AssignToConstant,
+                format!(
+                    "Cannot assign to constant '{}' (declared at line {}, column {}).",
+                    name, declared_at.line, declared_at.column
+                ),
+                span,
+            ));
+        }
+    }
+}
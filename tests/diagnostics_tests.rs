@@ -0,0 +1,73 @@
+use fddl::diagnostics::{render_json, render_plain, Diagnostic, Stage};
+use fddl::interpreter::evaluator::Evaluator;
+use fddl::lexer::Lexer;
+use fddl::lexer::token::Span;
+use fddl::parser::Parser;
+
+fn parse_diagnostics(source: &str) -> Vec<Diagnostic> {
+    let mut lexer = Lexer::new(source.to_string());
+    let tokens = lexer.scan_spanned_tokens();
+    let mut parser = Parser::new(tokens);
+    let errors = parser.parse_program().expect_err("program should fail to parse");
+    errors.iter().map(Diagnostic::from_parse_error).collect()
+}
+
+#[test]
+fn test_render_parse_error_with_caret() {
+    let source = "let x = 10\nprint x;";
+    let diagnostics = parse_diagnostics(source);
+
+    assert_eq!(
+        render_plain(&diagnostics[0], source, "main.fddl"),
+        "error[parser]: Expected ';' after variable declaration.\n \
+         --> main.fddl:2:1\n  \
+         |\n\
+         2 | print x;\n  \
+         | ^^^^^\n  \
+         = note: found keyword 'print'\n  \
+         = note: expected ';'\n"
+    );
+}
+
+#[test]
+fn test_lexer_errors_are_reported_from_the_lexer_stage() {
+    let diagnostics = parse_diagnostics("print \"unterminated;");
+
+    assert_eq!(diagnostics[0].stage, Stage::Lexer);
+    assert_eq!(diagnostics[0].message, "Unterminated string.");
+}
+
+#[test]
+fn test_unterminated_block_comment_is_a_lexer_error() {
+    let diagnostics = parse_diagnostics("let x = 1;\n/* never closed");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].stage, Stage::Lexer);
+    assert_eq!(diagnostics[0].message, "Unterminated block comment.");
+    assert_eq!(diagnostics[0].span.map(|span| (span.line, span.column)), Some((2, 1)));
+}
+
+#[test]
+fn test_runtime_error_points_at_failing_expression() {
+    let source = "let a = 1;\nprint a + nope;";
+    let mut lexer = Lexer::new(source.to_string());
+    let mut parser = Parser::new(lexer.scan_spanned_tokens());
+    let program = parser.parse_program().expect("program should parse");
+
+    let mut evaluator = Evaluator::new();
+    let error = evaluator.evaluate_program(program).expect_err("program should fail at runtime");
+    let diagnostic = Diagnostic::from_runtime_error(&error, evaluator.last_error_span());
+
+    assert_eq!(diagnostic.span, Some(Span::new(21, 25, 2, 11)));
+    assert!(render_plain(&diagnostic, source, "main.fddl").contains("2 | print a + nope;\n  |           ^^^^\n"));
+}
+
+#[test]
+fn test_render_json() {
+    let diagnostic = Diagnostic::error(Stage::Runtime, "bad \"value\"\n", None).with_help("try again");
+
+    assert_eq!(
+        render_json(&[diagnostic], "dir\\main.fddl"),
+        "[{\"severity\":\"error\",\"stage\":\"runtime\",\"message\":\"bad \\\"value\\\"\\n\",\"file\":\"dir\\\\main.fddl\",\"line\":null,\"column\":null,\"start\":null,\"end\":null,\"notes\":[],\"help\":\"try again\"}]"
+    );
+}
@@ -0,0 +1,1031 @@
+use fddl::lexer::Lexer;
+use fddl::parser::Parser;
+use fddl::interpreter::almost::AlmostMode;
+use fddl::interpreter::evaluator::{Evaluator, FddlValue, RuntimeError, Tolerance, STACK_SIZE};
+use fddl::interpreter::symbol::Symbol;
+
+fn try_run(source: &str) -> (Evaluator, Result<(), RuntimeError>) {
+    let mut lexer = Lexer::new(source.to_string());
+    let tokens = lexer.scan_spanned_tokens();
+    let mut parser = Parser::new(tokens);
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let mut evaluator = Evaluator::new();
+    let result = evaluator.evaluate_program(program_ast);
+    (evaluator, result)
+}
+
+fn run(source: &str) -> Evaluator {
+    let (evaluator, result) = try_run(source);
+    result.expect("program should run without runtime errors");
+    evaluator
+}
+
+#[test]
+fn test_if_else_takes_correct_branch() {
+    let evaluator = run("let a = 0; let b = 0; if (1 < 2) { a = 1; } else { a = 2; } if (nil) { b = 1; } else { b = 2; }");
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Number(1.0));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Number(2.0));
+}
+
+#[test]
+fn test_while_loop_runs_until_condition_is_falsey() {
+    let evaluator = run("let i = 0; let total = 0; while (i < 5) { total = total + i; i = i + 1; }");
+
+    assert_eq!(evaluator.get_variable("i").unwrap(), FddlValue::Number(5.0));
+    assert_eq!(evaluator.get_variable("total").unwrap(), FddlValue::Number(10.0));
+}
+
+#[test]
+fn test_for_loop_with_empty_clauses() {
+    let evaluator = run("let total = 0; for (let i = 0; i < 4;) { total = total + i; i = i + 1; }");
+
+    assert_eq!(evaluator.get_variable("total").unwrap(), FddlValue::Number(6.0));
+}
+
+#[test]
+fn test_for_loop_initializer_is_scoped_to_loop() {
+    let evaluator = run("let i = 100; for (let i = 0; i < 3;) { i = i + 1; }");
+
+    assert_eq!(evaluator.get_variable("i").unwrap(), FddlValue::Number(100.0));
+}
+
+#[test]
+fn test_function_call_returns_value() {
+    let evaluator = run("func add(a, b) { return a + b; } let sum = add(2, 3);");
+
+    assert_eq!(evaluator.get_variable("sum").unwrap(), FddlValue::Number(5.0));
+}
+
+#[test]
+fn test_return_unwinds_out_of_loops() {
+    let evaluator = run("func first_over(limit) { let i = 0; while (true) { if (i > limit) { return i; } i = i + 1; } } let found = first_over(3);");
+
+    assert_eq!(evaluator.get_variable("found").unwrap(), FddlValue::Number(4.0));
+}
+
+#[test]
+fn test_function_without_return_yields_nil() {
+    let evaluator = run("func noop() { let x = 1; } let result = noop();");
+
+    assert_eq!(evaluator.get_variable("result").unwrap(), FddlValue::Nil);
+}
+
+#[test]
+fn test_recursive_function() {
+    let evaluator = run("func fact(n) { if (n < 2) { return 1; } return n * fact(n - 1); } let result = fact(5);");
+
+    assert_eq!(evaluator.get_variable("result").unwrap(), FddlValue::Number(120.0));
+}
+
+#[test]
+fn test_runaway_recursion_is_a_stack_overflow_error() {
+    // Deep recursion needs the same big stack the `fddl` binary runs on.
+    let outcome = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| {
+            let (mut evaluator, result) = try_run("func forever(n) { return forever(n + 1); } forever(0);");
+            assert!(matches!(result, Err(RuntimeError::StackOverflow(name)) if name == "forever"));
+
+            // The depth unwinds with the error, so the same evaluator can still recurse deeply.
+            let source = "func count(n) { if (n == 0) { return 0; } return 1 + count(n - 1); } let a = count(1500);";
+            let program = Parser::new(Lexer::new(source.to_string()).scan_spanned_tokens()).parse_program().unwrap();
+            evaluator.evaluate_program(program).unwrap();
+            assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Int(1500));
+        })
+        .unwrap()
+        .join();
+    assert!(outcome.is_ok());
+}
+
+#[test]
+fn test_function_arity_mismatch_is_reported() {
+    let (_, result) = try_run("func add(a, b) { return a + b; } add(1);");
+
+    match result {
+        Err(RuntimeError::IncorrectArgumentCount { function_name, expected, found }) => {
+            assert_eq!(function_name, "add");
+            assert_eq!(expected, 2);
+            assert_eq!(found, 1);
+        }
+        other => panic!("Expected an argument count error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_closure_outlives_its_defining_scope() {
+    let evaluator = run("
+        func make_counter() {
+            let count = 0;
+            func increment() { count = count + 1; return count; }
+            return increment;
+        }
+        let counter = make_counter();
+        counter();
+        counter();
+        let result = counter();
+    ");
+
+    assert_eq!(evaluator.get_variable("result").unwrap(), FddlValue::Number(3.0));
+}
+
+#[test]
+fn test_separate_closures_get_separate_state() {
+    let evaluator = run("
+        func make_counter() {
+            let count = 0;
+            func increment() { count = count + 1; return count; }
+            return increment;
+        }
+        let first = make_counter();
+        let second = make_counter();
+        first();
+        first();
+        let a = first();
+        let b = second();
+    ");
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Number(3.0));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Number(1.0));
+}
+
+#[test]
+fn test_closures_share_captured_variable() {
+    let evaluator = run("
+        let set = nil;
+        let get = nil;
+        func make_cell() {
+            let value = 0;
+            func setter(v) { value = v; }
+            func getter() { return value; }
+            set = setter;
+            get = getter;
+        }
+        make_cell();
+        let before = get();
+        set(42);
+        let after = get();
+    ");
+
+    assert_eq!(evaluator.get_variable("before").unwrap(), FddlValue::Number(0.0));
+    assert_eq!(evaluator.get_variable("after").unwrap(), FddlValue::Number(42.0));
+}
+
+#[test]
+fn test_functions_use_lexical_not_dynamic_scope() {
+    let evaluator = run("
+        let x = \"global\";
+        func show() { return x; }
+        func caller() { let x = \"local\"; return show(); }
+        let result = caller();
+    ");
+
+    assert_eq!(evaluator.get_variable("result").unwrap(), FddlValue::String("global".to_string()));
+}
+
+#[test]
+fn test_template_interpolation() {
+    let evaluator = run(
+        "let n = 5; func square(x) { return x * x; } \
+         let a = `The square of $n is ${square($n)}`; \
+         let b = `${nil}, ${1 < 2}, ${square}, ${`nested $n`}`;",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::String("The square of 5 is 25".to_string()));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::String("nil, true, <func square>, nested 5".to_string()));
+}
+
+#[test]
+fn test_template_interpolation_propagates_errors() {
+    let (_, result) = try_run("let a = `value: ${missing}`;");
+
+    assert!(matches!(result, Err(RuntimeError::UndefinedVariable(message)) if message.contains("missing")));
+}
+
+#[test]
+fn test_string_concatenation() {
+    let evaluator = run(
+        "let a = \"foo\" + \"bar\"; \
+         let b = \"n = \" + 1.5; \
+         let c = 2 + \" items\"; \
+         let d = \"flag: \" + true; \
+         let e = \"\" + \"\";",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::String("foobar".to_string()));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::String("n = 1.5".to_string()));
+    assert_eq!(evaluator.get_variable("c").unwrap(), FddlValue::String("2 items".to_string()));
+    assert_eq!(evaluator.get_variable("d").unwrap(), FddlValue::String("flag: true".to_string()));
+    assert_eq!(evaluator.get_variable("e").unwrap(), FddlValue::String("".to_string()));
+}
+
+#[test]
+fn test_string_concatenation_rejects_nil_and_functions() {
+    for source in ["let a = \"x\" + nil;", "func f() { } let a = f + \"x\";", "let a = 1 + true;"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))), "source: {}", source);
+    }
+}
+
+#[test]
+fn test_type_errors_name_types_not_internals() {
+    let cases = [
+        ("let a = [1, 2] - 1;", "Operands for '-' must be numbers. Got list and int"),
+        ("let a = {\"k\": 1} * 2.5;", "Operands for '*' must be numbers, or a string and a number. Got map and float"),
+        ("let a = nil[0];", "Only lists and maps can be indexed. Got nil"),
+        ("let a = [1][0.5];", "List indices must be whole numbers. Got 0.5"),
+        ("let a = {}[[1]];", "Map keys must be strings, numbers, booleans, symbols or nil. Got list"),
+    ];
+    for (source, message) in cases {
+        let (_, result) = try_run(source);
+        match result {
+            Err(RuntimeError::TypeMismatch(got)) => assert_eq!(got, message, "source: {}", source),
+            other => panic!("expected a type mismatch for {:?}, got {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn test_string_repetition() {
+    let evaluator = run("let a = \"ab\" * 3; let b = 2 * \"-\"; let c = \"x\" * 0;");
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::String("ababab".to_string()));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::String("--".to_string()));
+    assert_eq!(evaluator.get_variable("c").unwrap(), FddlValue::String("".to_string()));
+
+    for source in ["let a = \"ab\" * -1;", "let a = \"ab\" * 1.5;", "let a = \"ab\" * \"cd\";"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))), "source: {}", source);
+    }
+
+    // Too long to build is an error rather than a failed allocation
+    for source in ["let a = \"ab\" * 100000000000000;", "let a = \"ab\" * 2 ^ 70;"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::StringTooLong(_))), "source: {}", source);
+    }
+    let evaluator = run("let a = \"\" * 2 ^ 70;");
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::String("".to_string()));
+}
+
+#[test]
+fn test_string_comparison_is_lexicographic() {
+    let evaluator = run(
+        "let a = \"apple\" < \"banana\"; \
+         let b = \"apple\" < \"app\"; \
+         let c = \"Zebra\" < \"apple\"; \
+         let d = \"same\" >= \"same\"; \
+         let e = \"b\" > \"abc\";",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Boolean(false));
+    assert_eq!(evaluator.get_variable("c").unwrap(), FddlValue::Boolean(true)); // by code point, so uppercase first
+    assert_eq!(evaluator.get_variable("d").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("e").unwrap(), FddlValue::Boolean(true));
+
+    let (_, result) = try_run("let a = \"1\" < 2;");
+    assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))));
+}
+
+#[test]
+fn test_almost_equal_numbers() {
+    let evaluator = run(
+        "let a = 0.1 + 0.2 ~= 0.3; \
+         let b = 1000000 ~= 1000000.5; \
+         let c = 1 ~= 1.1; \
+         let d = 0 ~= 0.0000000001;",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Boolean(true)); // within the relative epsilon
+    assert_eq!(evaluator.get_variable("c").unwrap(), FddlValue::Boolean(false));
+    assert_eq!(evaluator.get_variable("d").unwrap(), FddlValue::Boolean(true)); // within the absolute epsilon
+}
+
+#[test]
+fn test_almost_equal_strings_and_other_values() {
+    let evaluator = run(
+        "let a = \"Hello World\" ~= \"  hello\tworld \"; \
+         let b = \"hello\" ~= \"help\"; \
+         let c = nil ~= nil; \
+         let d = true ~= false; \
+         let e = \"1\" ~= 1;",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Boolean(false));
+    assert_eq!(evaluator.get_variable("c").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("d").unwrap(), FddlValue::Boolean(false));
+    assert_eq!(evaluator.get_variable("e").unwrap(), FddlValue::Boolean(false));
+}
+
+#[test]
+fn test_almost_equal_tolerance_is_configurable() {
+    let mut lexer = Lexer::new("let a = 1 ~= 1.05; let b = 100 ~= 104;".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_tolerance(Tolerance { absolute: 0.1, relative: 0.0 });
+    evaluator.evaluate_program(program_ast.clone()).expect("program should run");
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Boolean(false));
+
+    evaluator.set_tolerance(Tolerance { absolute: 0.0, relative: 0.05 });
+    evaluator.evaluate_program(program_ast).expect("program should run");
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Boolean(true));
+}
+
+fn almost_results(mode: AlmostMode) -> Vec<FddlValue> {
+    let mut lexer = Lexer::new("let a = ~10; let b = ~10; let c = ~0; let d = ~true;".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_almost_mode(mode);
+    evaluator.evaluate_program(program_ast).expect("program should run");
+    ["a", "b", "c", "d"].iter().map(|name| evaluator.get_variable(name).unwrap()).collect()
+}
+
+#[test]
+fn test_almost_bit_hash_mode_is_fixed_per_input() {
+    let results = almost_results(AlmostMode::BitHash);
+
+    assert_eq!(results[0], results[1]);
+    assert_eq!(results[2], FddlValue::Number(0.1337));
+    assert_eq!(results, almost_results(AlmostMode::default()));
+}
+
+#[test]
+fn test_almost_off_mode_leaves_numbers_alone() {
+    assert_eq!(
+        almost_results(AlmostMode::Off),
+        vec![FddlValue::Number(10.0), FddlValue::Number(10.0), FddlValue::Number(0.0), FddlValue::Boolean(false)]
+    );
+}
+
+#[test]
+fn test_almost_seeded_mode_is_reproducible() {
+    let seeded = |seed| almost_results(AlmostMode::Seeded { seed, magnitude: 0.1 });
+    let results = seeded(42);
+
+    assert_eq!(results, seeded(42));
+    assert_ne!(results, seeded(43));
+    assert_ne!(results[0], results[1]); // Each use of `~` draws a new value
+    for (value, center, spread) in [(&results[0], 10.0, 1.0), (&results[1], 10.0, 1.0), (&results[2], 0.0, 0.1)] {
+        match value {
+            FddlValue::Number(n) => assert!((n - center).abs() <= spread, "{} is too far from {}", n, center),
+            other => panic!("expected a number, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_const_can_be_read_and_shadowed() {
+    let evaluator = run("const a = 2; let b = a * 3; { let a = 10; a = 11; b = b + a; }");
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Number(2.0));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Number(17.0));
+}
+
+#[test]
+fn test_assigning_to_const_is_a_runtime_error() {
+    // The resolver can't see `limit` from inside `bump`, so this one is caught at runtime.
+    let (evaluator, result) = try_run("func bump() { limit = limit + 1; }\nconst limit = 1;\nbump();");
+
+    match result {
+        Err(RuntimeError::AssignToConstant { name, declared_at }) => {
+            assert_eq!(name, "limit");
+            assert_eq!((declared_at.line, declared_at.column), (2, 1));
+        }
+        other => panic!("expected AssignToConstant, got {:?}", other),
+    }
+    assert_eq!(evaluator.get_variable("limit").unwrap(), FddlValue::Number(1.0));
+}
+
+#[test]
+fn test_redeclaring_const_is_a_runtime_error() {
+    // Normally the resolver rejects this; the evaluator checks again for names it can't see.
+    for source in ["const a = 1;\nlet a = 2;", "const a = 1;\nfunc a() { }", "const a = 1;\nconst a = 2;"] {
+        let (evaluator, result) = try_run(source);
+        match result {
+            Err(RuntimeError::AssignToConstant { name, declared_at }) => {
+                assert_eq!(name, "a");
+                assert_eq!(declared_at.line, 1);
+            }
+            other => panic!("expected AssignToConstant for {:?}, got {:?}", source, other),
+        }
+        assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Number(1.0));
+    }
+}
+
+#[test]
+fn test_module_blocks_export_pub_members() {
+    let evaluator = run(
+        "let base = 10; \
+         module shapes { \
+             pub func area(w, h) { return w * h + base; } \
+             let hidden = 1; \
+             pub module inner { pub const depth = 2; } \
+         } \
+         let a = shapes.area(2, 3); \
+         let b = shapes.inner.depth;",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Number(16.0));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Number(2.0));
+    assert!(evaluator.get_variable("hidden").is_err()); // Module members don't leak into the enclosing scope
+}
+
+#[test]
+fn test_module_member_errors() {
+    let (_, result) = try_run("module m { let hidden = 1; } let a = m.hidden;");
+    assert!(matches!(result, Err(RuntimeError::PrivateMember { module, member }) if module == "m" && member == "hidden"));
+
+    let (_, result) = try_run("module m { } let a = m.missing;");
+    assert!(matches!(result, Err(RuntimeError::UndefinedMember { module, member }) if module == "m" && member == "missing"));
+
+    let (_, result) = try_run("let n = 1; let a = n.missing;");
+    assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))));
+}
+
+fn try_run_in(file: &str, source: &str) -> (Evaluator, Result<(), RuntimeError>) {
+    let mut lexer = Lexer::new(source.to_string());
+    let mut parser = Parser::new(lexer.scan_spanned_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_current_file(file);
+    let result = evaluator.evaluate_program(program_ast);
+    (evaluator, result)
+}
+
+#[test]
+fn test_imported_files_run_once_and_are_shared() {
+    let (evaluator, result) = try_run_in(
+        "tests/fixtures/modules/main.fddl",
+        "import counter; import \"lib/helper\"; let a = counter.bump(); let b = helper.bump_twice();",
+    );
+    result.expect("program should run");
+
+    // helper imports the same counter.fddl, so it bumps the same count instead of a fresh copy.
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Number(1.0));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Number(3.0));
+}
+
+#[test]
+fn test_import_errors() {
+    let (_, result) = try_run_in("tests/fixtures/modules/main.fddl", "import cycle_a;");
+    match result {
+        Err(RuntimeError::ImportCycle(paths)) => {
+            let names: Vec<&str> = paths.iter().map(|path| path.rsplit('/').next().unwrap()).collect();
+            assert_eq!(names, vec!["cycle_a.fddl", "cycle_b.fddl", "cycle_a.fddl"]);
+        }
+        other => panic!("expected an import cycle, got {:?}", other),
+    }
+
+    let (_, result) = try_run_in("tests/fixtures/modules/main.fddl", "import missing;");
+    assert!(matches!(result, Err(RuntimeError::ImportFailed { path, .. }) if path.ends_with("missing.fddl")));
+
+    let (_, result) = try_run_in("tests/fixtures/modules/main.fddl", "import broken;");
+    assert!(matches!(result, Err(RuntimeError::ImportFailed { message, .. }) if message.starts_with("[line 1, column 13]")));
+}
+
+#[test]
+fn test_errors_inside_imported_functions_point_at_the_call() {
+    let (evaluator, result) = try_run_in(
+        "tests/fixtures/modules/main.fddl",
+        "import failing;\nfunc run() { return failing.fail(); }\nlet x = 1;\nrun();",
+    );
+    assert!(matches!(result, Err(RuntimeError::DivisionByZero)));
+    // failing.fddl's line 5 would be meaningless here; the call from this file is blamed instead.
+    let span = evaluator.last_error_span().expect("error should have a span");
+    assert_eq!((span.line, span.column), (2, 21));
+}
+
+#[test]
+fn test_symbols_compare_by_identity() {
+    let evaluator = run(
+        "func pick(n) { if (n > 0) { return sym positive; } return sym other; } \
+         let a = pick(1) == sym positive; \
+         let b = pick(0) == sym positive; \
+         let c = sym red == \"red\"; \
+         let d = sym red != sym Red; \
+         let e = `state: ${pick(1)}`; \
+         let f = sym done;",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Boolean(false));
+    assert_eq!(evaluator.get_variable("c").unwrap(), FddlValue::Boolean(false));
+    assert_eq!(evaluator.get_variable("d").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("e").unwrap(), FddlValue::String("state: :positive".to_string()));
+    assert_eq!(evaluator.get_variable("f").unwrap(), FddlValue::Symbol(Symbol::intern("done")));
+}
+
+#[test]
+fn test_symbols_are_interned() {
+    assert_eq!(Symbol::intern("tag"), Symbol::intern("tag"));
+    assert_ne!(Symbol::intern("tag"), Symbol::intern("other_tag"));
+    assert_eq!(&*Symbol::intern("tag").name(), "tag");
+}
+
+#[test]
+fn test_list_indexing() {
+    let evaluator = run(
+        "let xs = [10, \"b\", [true, nil]]; \
+         let first = xs[0]; \
+         let last = xs[-1][0]; \
+         let middle = xs[-2]; \
+         xs[1] = sym c; \
+         xs[-1][1] = 5; \
+         let shown = `${xs}`;",
+    );
+
+    assert_eq!(evaluator.get_variable("first").unwrap(), FddlValue::Number(10.0));
+    assert_eq!(evaluator.get_variable("last").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("middle").unwrap(), FddlValue::String("b".to_string()));
+    assert_eq!(evaluator.get_variable("shown").unwrap(), FddlValue::String("[10, :c, [true, 5]]".to_string()));
+}
+
+#[test]
+fn test_lists_are_shared_by_reference() {
+    let evaluator = run(
+        "func push_zero(list) { list[0] = 0; } \
+         let xs = [1, 2]; \
+         let ys = xs; \
+         push_zero(ys); \
+         let same = xs == ys; \
+         let copy_equal = [0, 2] == xs;",
+    );
+
+    assert_eq!(evaluator.get_variable("xs").unwrap().to_string(), "[0, 2]");
+    assert_eq!(evaluator.get_variable("same").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("copy_equal").unwrap(), FddlValue::Boolean(true)); // `==` compares contents
+}
+
+#[test]
+fn test_list_index_errors() {
+    let (_, result) = try_run("let xs = [1, 2]; let a = xs[2];");
+    assert!(matches!(result, Err(RuntimeError::IndexOutOfBounds { index, length: 2 }) if index == 2));
+
+    let (_, result) = try_run("let xs = [1, 2]; xs[-3] = 0;");
+    assert!(matches!(result, Err(RuntimeError::IndexOutOfBounds { index, length: 2 }) if index == -3));
+
+    for source in ["let xs = [1]; let a = xs[0.5];", "let xs = [1]; let a = xs[\"0\"];", "let n = 1; let a = n[0];"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))), "source: {}", source);
+    }
+}
+
+#[test]
+fn test_list_containing_itself_prints() {
+    let evaluator = run("let xs = [1]; xs[0] = xs;");
+
+    assert_eq!(evaluator.get_variable("xs").unwrap().to_string(), "[[...]]");
+}
+
+#[test]
+fn test_lists_containing_themselves_compare() {
+    let evaluator = run(
+        "let a = [1]; a[0] = a; let b = [1]; b[0] = b; let c = [2, nil]; c[1] = c; \
+         let same = a == a; let twins = a == b; let different = a == c; let copy = [a] == [b];",
+    );
+
+    assert_eq!(evaluator.get_variable("same").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("twins").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("different").unwrap(), FddlValue::Boolean(false));
+    assert_eq!(evaluator.get_variable("copy").unwrap(), FddlValue::Boolean(true));
+}
+
+#[test]
+fn test_maps_containing_themselves_compare() {
+    let evaluator = run(
+        "let m = {}; m.self = m; let n = {}; n.self = n; let o = { other: 1 }; o.self = o; \
+         let same = m == m; let twins = m == n; let different = m == o; let nested = { a: m } == { a: n };",
+    );
+
+    assert_eq!(evaluator.get_variable("same").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("twins").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("different").unwrap(), FddlValue::Boolean(false));
+    assert_eq!(evaluator.get_variable("nested").unwrap(), FddlValue::Boolean(true));
+}
+
+#[test]
+fn test_map_lookup_and_update() {
+    let evaluator = run(
+        "let key = \"dynamic\"; \
+         let m = { name: \"fddl\", \"version\": 1, 2: \"two\", sym tag: true }; \
+         let a = m.name; \
+         let b = m[\"version\"]; \
+         let c = m[2]; \
+         let d = m[sym tag]; \
+         let e = m.missing; \
+         m.version = 2; \
+         m[key] = [1]; \
+         m[true] = nil;",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::String("fddl".to_string()));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Number(1.0));
+    assert_eq!(evaluator.get_variable("c").unwrap(), FddlValue::String("two".to_string()));
+    assert_eq!(evaluator.get_variable("d").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("e").unwrap(), FddlValue::Nil);
+    // Entries stay in insertion order; updating `version` doesn't move it.
+    assert_eq!(
+        evaluator.get_variable("m").unwrap().to_string(),
+        "{\"name\": \"fddl\", \"version\": 2, 2: \"two\", :tag: true, \"dynamic\": [1], true: nil}"
+    );
+}
+
+#[test]
+fn test_maps_are_shared_and_compare_by_contents() {
+    let evaluator = run(
+        "func rename(record) { record.name = \"new\"; } \
+         let m = { name: \"old\", id: 1 }; \
+         rename(m); \
+         let same = m == { id: 1, name: \"new\" }; \
+         let different = m == { id: 1 }; \
+         let zero = { 0: \"zero\" }[-0];",
+    );
+
+    assert_eq!(evaluator.get_variable("same").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("different").unwrap(), FddlValue::Boolean(false));
+    assert_eq!(evaluator.get_variable("zero").unwrap(), FddlValue::String("zero".to_string()));
+}
+
+#[test]
+fn test_map_key_errors() {
+    for source in ["let m = { [1]: 2 };", "let m = {}; m[{}] = 1;", "let n = 1; n.field = 2;", "module mod { } mod.x = 1;"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))), "source: {}", source);
+    }
+}
+
+#[test]
+fn test_for_in_over_lists_strings_and_ranges() {
+    let evaluator = run(
+        "let items = \"\"; for (x in [1, sym two, \"three\"]) { items = items + `${x};`; } \
+         let pairs = \"\"; for (i, c in \"hey\") { pairs = pairs + `${i}${c}`; } \
+         let exclusive = 0; for (i in 0..5) { exclusive = exclusive + i; } \
+         let inclusive = 0; for (i in 1..=5) { inclusive = inclusive + i; } \
+         let empty = 0; for (i in 3..3) { empty = empty + 1; } \
+         let r = 2..=4;",
+    );
+
+    assert_eq!(evaluator.get_variable("items").unwrap(), FddlValue::String("1;:two;three;".to_string()));
+    assert_eq!(evaluator.get_variable("pairs").unwrap(), FddlValue::String("0h1e2y".to_string()));
+    assert_eq!(evaluator.get_variable("exclusive").unwrap(), FddlValue::Number(10.0));
+    assert_eq!(evaluator.get_variable("inclusive").unwrap(), FddlValue::Number(15.0));
+    assert_eq!(evaluator.get_variable("empty").unwrap(), FddlValue::Number(0.0));
+    assert_eq!(evaluator.get_variable("r").unwrap().to_string(), "2..=4");
+}
+
+#[test]
+fn test_for_in_over_maps_binds_keys_or_entries() {
+    let evaluator = run(
+        "let m = { b: 2, a: 1, sym c: 3 }; \
+         let keys = \"\"; for (k in m) { keys = keys + `${k} `; } \
+         let total = 0; let order = \"\"; for (k, v in m) { total = total + v; order = order + `${k}`; }",
+    );
+
+    assert_eq!(evaluator.get_variable("keys").unwrap(), FddlValue::String("b a :c ".to_string()));
+    assert_eq!(evaluator.get_variable("total").unwrap(), FddlValue::Number(6.0));
+    assert_eq!(evaluator.get_variable("order").unwrap(), FddlValue::String("ba:c".to_string()));
+}
+
+#[test]
+fn test_for_in_scoping_and_early_return() {
+    let evaluator = run(
+        "let x = \"outer\"; for (x in [1, 2]) { } \
+         func first_over(xs, limit) { for (n in xs) { if (n > limit) { return n; } } return nil; } \
+         let found = first_over([3, 5, 8, 9], 6); \
+         let big = 0; func stop_early() { for (i in 0..1000000000000) { big = i; if (i == 3) { return i; } } } \
+         stop_early();",
+    );
+
+    assert_eq!(evaluator.get_variable("x").unwrap(), FddlValue::String("outer".to_string()));
+    assert_eq!(evaluator.get_variable("found").unwrap(), FddlValue::Number(8.0));
+    assert_eq!(evaluator.get_variable("big").unwrap(), FddlValue::Number(3.0));
+}
+
+#[test]
+fn test_for_in_errors() {
+    for source in ["for (x in 5) { }", "for (x in nil) { }", "let r = 0..\"ten\";", "let r = ~(0..1);"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))), "source: {}", source);
+    }
+}
+
+#[test]
+fn test_break_leaves_the_innermost_loop() {
+    let evaluator = run(
+        "let i = 0; while (true) { i = i + 1; if (i == 3) { break; } } \
+         let pairs = 0; for (a in 0..3) { for (b in 0..10) { if (b == 2) { break; } pairs = pairs + 1; } } \
+         let last = nil; for (let j = 0; j < 10;) { last = j; if (j == 4) { break; } j = j + 1; }",
+    );
+
+    assert_eq!(evaluator.get_variable("i").unwrap(), FddlValue::Number(3.0));
+    assert_eq!(evaluator.get_variable("pairs").unwrap(), FddlValue::Number(6.0));
+    assert_eq!(evaluator.get_variable("last").unwrap(), FddlValue::Number(4.0));
+}
+
+#[test]
+fn test_continue_skips_to_the_next_pass() {
+    let evaluator = run(
+        "let odd = 0; for (let i = 0; i < 6;) { i = i + 1; if (i == 2 or i == 4 or i == 6) { continue; } odd = odd + i; } \
+         let skipped = \"\"; for (c in \"a-b-c\") { if (c == \"-\") { continue; } skipped = skipped + c; } \
+         let n = 0; let seen = 0; while (n < 5) { n = n + 1; if (n > 2) { continue; } seen = seen + 1; } \
+         let steps = 0; func step() { steps = steps + 1; } for (; steps < 5; step()) { continue; }",
+    );
+
+    assert_eq!(evaluator.get_variable("odd").unwrap(), FddlValue::Number(9.0));
+    assert_eq!(evaluator.get_variable("skipped").unwrap(), FddlValue::String("abc".to_string()));
+    assert_eq!(evaluator.get_variable("seen").unwrap(), FddlValue::Number(2.0));
+    // `continue` in a C-style loop still runs the increment.
+    assert_eq!(evaluator.get_variable("steps").unwrap(), FddlValue::Number(5.0));
+}
+
+#[test]
+fn test_return_from_inside_a_loop_still_returns() {
+    let evaluator = run("func find(xs) { for (x in xs) { while (true) { return x; } } return nil; } let found = find([7, 8]);");
+
+    assert_eq!(evaluator.get_variable("found").unwrap(), FddlValue::Number(7.0));
+}
+
+#[test]
+fn test_compound_assignment() {
+    let evaluator = run(
+        "let n = 10; n += 5; n -= 3; n *= 2; n /= 4; n %= 4; \
+         let s = \"a\"; s += \"b\"; s *= 2; \
+         let xs = [1, 2]; xs[-1] += 10; \
+         let m = { count: 1 }; m.count += 1; m[\"count\"] *= 5; m.fresh = m.other = 7; \
+         let chained = nil; let y = 1; chained = y += 1;",
+    );
+
+    assert_eq!(evaluator.get_variable("n").unwrap(), FddlValue::Number(2.0));
+    assert_eq!(evaluator.get_variable("s").unwrap(), FddlValue::String("abab".to_string()));
+    assert_eq!(evaluator.get_variable("xs").unwrap().to_string(), "[1, 12]");
+    assert_eq!(evaluator.get_variable("m").unwrap().to_string(), "{\"count\": 10, \"other\": 7, \"fresh\": 7}");
+    assert_eq!(evaluator.get_variable("chained").unwrap(), FddlValue::Number(2.0));
+    assert_eq!(evaluator.get_variable("y").unwrap(), FddlValue::Number(2.0));
+}
+
+#[test]
+fn test_assignments_in_for_loop_increment() {
+    let evaluator = run(
+        "let total = 0; for (let i = 0; i < 5; i += 1) { total += i; } \
+         let powers = \"\"; for (let p = 1; p < 20; p = p * 2) { powers += `${p} `; }",
+    );
+
+    assert_eq!(evaluator.get_variable("total").unwrap(), FddlValue::Number(10.0));
+    assert_eq!(evaluator.get_variable("powers").unwrap(), FddlValue::String("1 2 4 8 16 ".to_string()));
+}
+
+#[test]
+fn test_compound_assignment_errors() {
+    let (_, result) = try_run("let m = {}; m.missing -= 1;");
+    assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))));
+
+    let (_, result) = try_run("let xs = [1]; xs[1] += 1;");
+    assert!(matches!(result, Err(RuntimeError::IndexOutOfBounds { .. })));
+
+    let (_, result) = try_run("let n = 1; n /= 0;");
+    assert!(matches!(result, Err(RuntimeError::DivisionByZero)));
+}
+
+#[test]
+fn test_expression_bodied_functions() {
+    let evaluator = run("func square(x) => x * x; module math { pub func cube(x) => x * square(x); } let a = square(4); let b = math.cube(2);");
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Number(16.0));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Number(8.0));
+}
+
+#[test]
+fn test_anonymous_functions_are_values() {
+    let evaluator = run(
+        "func apply_twice(f, x) => f(f(x)); \
+         let a = apply_twice(func (n) => n * 3, 2); \
+         func adder(by) => func (n) => n + by; \
+         let add5 = adder(5); let b = add5(10); \
+         let c = (func (x, y) { let total = x + y; return total * 2; })(1, 2); \
+         let ops = { double: func (n) => n * 2 }; let d = ops.double(21); \
+         let shown = `${add5}`;",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Number(18.0));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Number(15.0));
+    assert_eq!(evaluator.get_variable("c").unwrap(), FddlValue::Number(6.0));
+    assert_eq!(evaluator.get_variable("d").unwrap(), FddlValue::Number(42.0));
+    assert_eq!(evaluator.get_variable("shown").unwrap(), FddlValue::String("<func anonymous>".to_string()));
+}
+
+#[test]
+fn test_anonymous_functions_capture_each_loop_pass() {
+    let evaluator = run(
+        "let callbacks = [nil, nil, nil]; for (i in 0..3) { callbacks[i] = func () => i * 10; } \
+         let total = 0; for (f in callbacks) { total += f(); }",
+    );
+
+    assert_eq!(evaluator.get_variable("total").unwrap(), FddlValue::Number(30.0));
+}
+
+#[test]
+fn test_exponent_and_integer_division() {
+    let evaluator = run(
+        "let a = 2 ^ 10; let b = -2 ^ 2; let c = 2 ^ 3 ^ 2; let d = 4 ^ 0.5; let e = 2 ^ -1; \
+         let f = 7 div 2; let g = -7 div 2; let h = 7.5 div 2; \
+         func square(x) => x ^ 2; let i = square(5);",
+    );
+
+    for (name, expected) in [
+        ("a", 1024.0),
+        ("b", -4.0),
+        ("c", 512.0),
+        ("d", 2.0),
+        ("e", 0.5),
+        ("f", 3.0),
+        ("g", -4.0),
+        ("h", 3.0),
+        ("i", 25.0),
+    ] {
+        assert_eq!(evaluator.get_variable(name).unwrap(), FddlValue::Number(expected), "variable {}", name);
+    }
+}
+
+#[test]
+fn test_modulus_rounds_down_like_div() {
+    // The remainder takes the divisor's sign, so `(a div b) * b + a % b == a`.
+    let evaluator = run(
+        "let a = -5 % 3; let b = 5 % -3; let c = -5 % -3; let d = (-5 div 3) * 3 + -5 % 3; \
+         let e = -5.5 % 2; let f = 5.5 % -2; let g = -(2 ^ 70) % 3; let h = 2 ^ 70 % -3; \
+         let i = -9223372036854775807 - 1; let j = i % -1; let k = -6 % 3;",
+    );
+
+    for (name, expected) in [
+        ("a", "1"),
+        ("b", "-1"),
+        ("c", "-2"),
+        ("d", "-5"),
+        ("e", "0.5"),
+        ("f", "-0.5"),
+        ("g", "2"),
+        ("h", "-2"),
+        ("j", "0"),
+        ("k", "0"),
+    ] {
+        assert_eq!(evaluator.get_variable(name).unwrap().to_string(), expected, "variable {}", name);
+    }
+}
+
+#[test]
+fn test_bitwise_operators() {
+    let evaluator = run(
+        "let a = 6 & 3; let b = 6 | 3; let c = 6 ~ 3; let d = 1 << 10; let e = -8 >> 1; \
+         let f = 5 & 1 == 1; let g = 1 | 2 ~ 3 & 4;",
+    );
+
+    for (name, expected) in [("a", 2.0), ("b", 7.0), ("c", 5.0), ("d", 1024.0), ("e", -4.0), ("g", 3.0)] {
+        assert_eq!(evaluator.get_variable(name).unwrap(), FddlValue::Number(expected), "variable {}", name);
+    }
+    assert_eq!(evaluator.get_variable("f").unwrap(), FddlValue::Boolean(true));
+}
+
+#[test]
+fn test_operator_errors() {
+    for source in ["1.5 & 1;", "1 | \"a\";", "1 << 64;", "1 >> -1;", "\"a\" ^ 2;", "nil div 2;"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))), "source: {}", source);
+    }
+
+    let (_, result) = try_run("1 div 0;");
+    assert!(matches!(result, Err(RuntimeError::DivisionByZero)));
+}
+
+#[test]
+fn test_integer_and_float_arithmetic() {
+    let evaluator = run(
+        "let a = 1 + 2; let b = 10 / 3; let c = 1 + 2.0; let d = 7 % 3; let e = 2 ^ 62; \
+         let f = 1 == 1.0; let g = 2 < 2.5; let h = 6 / 2; let i = [10, 20][1.0]; \
+         let m = {}; m[1] = \"one\"; let j = m[1.0]; let n = 0; for (k in 0..3) { n = k; }",
+    );
+
+    assert!(matches!(evaluator.get_variable("a").unwrap(), FddlValue::Int(3)));
+    assert!(matches!(evaluator.get_variable("b").unwrap(), FddlValue::Number(n) if n == 10.0 / 3.0));
+    assert!(matches!(evaluator.get_variable("c").unwrap(), FddlValue::Number(n) if n == 3.0));
+    assert!(matches!(evaluator.get_variable("d").unwrap(), FddlValue::Int(1)));
+    assert!(matches!(evaluator.get_variable("e").unwrap(), FddlValue::Int(4611686018427387904)));
+    assert_eq!(evaluator.get_variable("f").unwrap(), FddlValue::Boolean(true));
+    assert_eq!(evaluator.get_variable("g").unwrap(), FddlValue::Boolean(true));
+    assert!(matches!(evaluator.get_variable("i").unwrap(), FddlValue::Int(20)));
+    assert_eq!(evaluator.get_variable("j").unwrap(), FddlValue::String("one".to_string()));
+    assert!(matches!(evaluator.get_variable("n").unwrap(), FddlValue::Int(2)));
+
+    // Whole floats keep their ".0" when printed; ints don't get one
+    assert_eq!(evaluator.get_variable("a").unwrap().to_string(), "3");
+    assert_eq!(evaluator.get_variable("h").unwrap().to_string(), "3.0");
+}
+
+#[test]
+fn test_integer_overflow_promotes_to_big_integers() {
+    let evaluator = run(
+        "let a = 9223372036854775807 + 1; let b = -9223372036854775807 - 2; let c = 4294967296 * 4294967296; \
+         let d = 2 ^ 100; let e = 1 << 63; let f = -(-9223372036854775807 - 1); \
+         let g = 100000000000000000000 div 7; let h = 100000000000000000000 % 7; let i = -d div 3; \
+         let j = d - d + 5; let k = d / 2 ^ 99; let l = 170141183460469231731687303715884105727 * 2 + 1; \
+         let m = a > 9223372036854775807; let n = d == 2.0 ^ 100; let o = a < float(\"inf\"); \
+         let total = 1; for (x in 1..=25) { total *= x; }",
+    );
+
+    for (name, expected) in [
+        ("a", "9223372036854775808"),
+        ("b", "-9223372036854775809"),
+        ("c", "18446744073709551616"),
+        ("d", "1267650600228229401496703205376"),
+        ("e", "9223372036854775808"),
+        ("f", "9223372036854775808"),
+        ("g", "14285714285714285714"),
+        ("h", "2"),
+        ("i", "-422550200076076467165567735126"),
+        ("j", "5"),
+        ("k", "2.0"),
+        ("l", "340282366920938463463374607431768211455"),
+        ("m", "true"),
+        ("n", "true"),
+        ("o", "true"),
+        ("total", "15511210043330985984000000"),
+    ] {
+        assert_eq!(evaluator.get_variable(name).unwrap().to_string(), expected, "variable {}", name);
+    }
+    // Results that fit in 64 bits again are ordinary ints
+    assert!(matches!(evaluator.get_variable("h").unwrap(), FddlValue::Int(2)));
+    assert!(matches!(evaluator.get_variable("j").unwrap(), FddlValue::Int(5)));
+    assert!(matches!(evaluator.get_variable("d").unwrap(), FddlValue::BigInt(_)));
+
+    let evaluator = run("let m = {}; m[2 ^ 70] = 1; let a = m[2 ^ 70]; let b = m[2.0 ^ 70]; let c = int(\"-123456789012345678901234567890\");");
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Int(1));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Int(1));
+    assert_eq!(evaluator.get_variable("c").unwrap().to_string(), "-123456789012345678901234567890");
+
+    let (_, result) = try_run("10 ^ 10 ^ 10;");
+    assert!(matches!(result, Err(RuntimeError::IntegerTooLarge(_))));
+    for source in ["(2 ^ 64) & 1;", "1 << (2 ^ 64);"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))), "source: {}", source);
+    }
+    // Big ints divide exactly before rounding, even past the float range
+    let evaluator = run("let a = 10 ^ 400 / 10 ^ 399; let b = -(10 ^ 400) / (3 * 10 ^ 399); let c = 9007199254740993 * 2 ^ 64 / 2 ^ 64;");
+    assert_eq!(evaluator.get_variable("a").unwrap().to_string(), "10.0");
+    assert!(matches!(evaluator.get_variable("b").unwrap(), FddlValue::Number(n) if n == -10.0 / 3.0));
+    assert!(matches!(evaluator.get_variable("c").unwrap(), FddlValue::Number(n) if n == 9007199254740992.0));
+
+    for source in ["(2 ^ 64) div 0;", "(2 ^ 64) / 0;"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::DivisionByZero)), "source: {}", source);
+    }
+}
+
+#[test]
+fn test_int_and_float_builtins() {
+    let evaluator = run(
+        "let a = int(3.9); let b = int(-3.9); let c = int(\" 42 \"); let d = int(true); \
+         let e = float(2); let f = float(\"2.5\"); let g = int; \
+         func shadow() { let int = 5; return int; } let h = shadow();",
+    );
+
+    for (name, expected) in [("a", 3), ("b", -3), ("c", 42), ("d", 1), ("h", 5)] {
+        assert!(
+            matches!(evaluator.get_variable(name).unwrap(), FddlValue::Int(n) if n == expected),
+            "variable {}",
+            name
+        );
+    }
+    assert_eq!(evaluator.get_variable("e").unwrap().to_string(), "2.0");
+    assert!(matches!(evaluator.get_variable("f").unwrap(), FddlValue::Number(n) if n == 2.5));
+    assert_eq!(evaluator.get_variable("g").unwrap().to_string(), "<builtin int>");
+
+    for source in ["int(\"abc\");", "int(nil);", "float([1]);", "int(float(\"inf\"));"] {
+        let (_, result) = try_run(source);
+        assert!(matches!(result, Err(RuntimeError::TypeMismatch(_))), "source: {}", source);
+    }
+    let (_, result) = try_run("int(1, 2);");
+    assert!(matches!(result, Err(RuntimeError::IncorrectArgumentCount { expected: 1, found: 2, .. })));
+}
+
+#[test]
+fn test_extended_number_literals() {
+    let evaluator = run(
+        "let a = 0xff + 0b11 + 0o10; let b = 1_000 * 2; let c = 1.5e3; let d = 0xffff_ffff_ffff_ffff + 1; \
+         let e = int(\"0x1f\"); let f = -0x8000_0000_0000_0000;",
+    );
+
+    assert_eq!(evaluator.get_variable("a").unwrap(), FddlValue::Int(266));
+    assert_eq!(evaluator.get_variable("b").unwrap(), FddlValue::Int(2000));
+    assert_eq!(evaluator.get_variable("c").unwrap().to_string(), "1500.0");
+    assert_eq!(evaluator.get_variable("d").unwrap().to_string(), "18446744073709551616");
+    assert_eq!(evaluator.get_variable("e").unwrap(), FddlValue::Int(31));
+    assert!(matches!(evaluator.get_variable("f").unwrap(), FddlValue::Int(i64::MIN)));
+}
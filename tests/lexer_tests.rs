@@ -1,5 +1,5 @@
 use fddl::lexer::Lexer;
-use fddl::lexer::token::Token;
+use fddl::lexer::token::{Span, SpannedToken, TemplatePart, Token};
 
 #[test]
 fn test_single_tokens() {
@@ -34,7 +34,7 @@ fn test_keywords_and_identifiers() {
             Token::Sym,
             Token::Identifier("myVar".to_string()),
             Token::Equal,
-            Token::Number(123.0),
+            Token::Integer(123),
             Token::Semicolon,
             Token::EOF
         ]
@@ -58,7 +58,7 @@ fn test_pub_keyword() {
             Token::RightParen,
             Token::LeftBrace,
             Token::Return,
-            Token::Number(42.0),
+            Token::Integer(42),
             Token::Semicolon,
             Token::RightBrace,
             Token::EOF
@@ -79,7 +79,7 @@ fn test_comments() {
             Token::Let,
             Token::Identifier("a".to_string()),
             Token::Equal,
-            Token::Number(5.0),
+            Token::Integer(5),
             Token::Semicolon,
             Token::EOF
         ]
@@ -97,11 +97,11 @@ fn test_operators_and_comparison() {
         vec![
             Token::Identifier("a".to_string()),
             Token::GreaterEqual,
-            Token::Number(10.0),
+            Token::Integer(10),
             Token::BangEqual,
             Token::Identifier("b".to_string()),
             Token::EqualEqual,
-            Token::Number(5.0),
+            Token::Integer(5),
             Token::Semicolon,
             Token::EOF
         ]
@@ -128,7 +128,7 @@ fn test_tilde_operator() {
             Token::Identifier("c".to_string()),
             Token::Equal,
             Token::Tilde,
-            Token::Number(5.0),
+            Token::Integer(5),
             Token::Semicolon,
             Token::RightBrace,
             Token::EOF
@@ -145,9 +145,9 @@ fn test_modulus_operator() {
     assert_eq!(
         tokens,
         vec![
-            Token::Number(10.0),
+            Token::Integer(10),
             Token::Percent,
-            Token::Number(3.0),
+            Token::Integer(3),
             Token::Semicolon,
             Token::EOF
         ]
@@ -224,7 +224,7 @@ fn test_block_comments() {
             Token::Let,
             Token::Identifier("x".to_string()),
             Token::Equal,
-            Token::Number(1.0),
+            Token::Integer(1),
             Token::Semicolon,
             Token::Comment(" another one ".to_string()), // Content might vary
             Token::EOF
@@ -264,7 +264,7 @@ fn test_empty_block_comment() {
         vec![
             Token::Comment("".to_string()), // Empty content
             Token::Print,
-            Token::Number(1.0),
+            Token::Integer(1),
             Token::Semicolon,
             Token::EOF
         ]
@@ -273,33 +273,25 @@ fn test_empty_block_comment() {
 
 #[test]
 fn test_block_comment_at_eof() {
-    let source = String::from("let z = 10; /* block comment at eof"); // Unterminated
+    let source = String::from("let z = 10; /* block comment\nat eof"); // Unterminated
     let mut lexer = Lexer::new(source.clone());
-    let tokens = lexer.scan_tokens();
+    let tokens = lexer.scan_spanned_tokens();
 
-    // This depends on how your lexer handles unterminated block comments.
-    // The consume_block_comment function we discussed would try to form a comment
-    // with what it has or an error.
-    // If it forms a comment with the partial content:
+    let kinds: Vec<Token> = tokens.iter().map(|spanned| spanned.token.clone()).collect();
     assert_eq!(
-        tokens,
+        kinds,
         vec![
             Token::Let,
             Token::Identifier("z".to_string()),
             Token::Equal,
-            Token::Number(10.0),
+            Token::Integer(10),
             Token::Semicolon,
-            Token::Comment(" block comment at eof".to_string()), // Or whatever your lexer emits for unterminated
-            Token::EOF                                          // EOF is always last
+            Token::Error("Unterminated block comment.".to_string()),
+            Token::EOF
         ]
-        // Alternative if it produces an error token for unterminated comments:
-        // vec![
-        //     ...,
-        //     Token::Semicolon,
-        //     Token::Error("Unterminated block comment".to_string()), // Or similar
-        //     Token::EOF
-        // ]
     );
+    // The error covers the whole comment, starting at its `/*`.
+    assert_eq!(tokens[5].span, Span::new(12, 35, 1, 13));
 }
 
 #[test]
@@ -317,9 +309,9 @@ fn test_combined_new_tokens() {
             Token::Some,
             Token::Identifier("value".to_string()),
             Token::Percent,
-            Token::Number(2.0),
+            Token::Integer(2),
             Token::EqualEqual,
-            Token::Number(0.0),
+            Token::Integer(0),
             Token::And,
             Token::Not,
             Token::Nil,
@@ -327,4 +319,374 @@ fn test_combined_new_tokens() {
             Token::EOF
         ]
     );
-}
\ No newline at end of file
+}
+#[test]
+fn test_token_spans() {
+    let source = String::from("let é = \"hi\";\n  print é;");
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.scan_spanned_tokens();
+
+    let spans: Vec<Span> = tokens.iter().map(|spanned| spanned.span).collect();
+    assert_eq!(
+        spans,
+        vec![
+            Span::new(0, 3, 1, 1),   // let
+            Span::new(4, 6, 1, 5),   // é (two bytes)
+            Span::new(7, 8, 1, 7),   // =
+            Span::new(9, 13, 1, 9),  // "hi"
+            Span::new(13, 14, 1, 13), // ;
+            Span::new(17, 22, 2, 3), // print
+            Span::new(23, 25, 2, 9), // é
+            Span::new(25, 26, 2, 10), // ; (columns count chars, not bytes)
+            Span::new(26, 26, 2, 11), // EOF
+        ]
+    );
+}
+
+#[test]
+fn test_string_escapes() {
+    let source = String::from(r#""a\nb\t\"q\"\\\r\0 \u{48}\u{1F600}""#);
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::StringLiteral("a\nb\t\"q\"\\\r\0 H\u{1F600}".to_string()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_string_escapes() {
+    let cases = [
+        (r#""bad \q escape""#, "Invalid escape sequence '\\q'."),
+        (r#""\u0041""#, "Expected '{' after '\\u' in unicode escape."),
+        (r#""\u{41""#, "Expected '}' to close unicode escape."),
+        (r#""\u{}""#, "Unicode escape '\\u{}' must have 1 to 6 hex digits."),
+        (r#""\u{1234567}""#, "Unicode escape '\\u{1234567}' must have 1 to 6 hex digits."),
+        (r#""\u{D800}""#, "'\\u{D800}' is not a valid unicode character."),
+    ];
+
+    for (source, message) in cases {
+        let mut lexer = Lexer::new(source.to_string());
+        let tokens = lexer.scan_spanned_tokens();
+        assert_eq!(tokens[0].token, Token::Error(message.to_string()), "source: {}", source);
+        // The error covers the whole literal, up to and including the closing quote.
+        assert_eq!(tokens[0].span.end, source.len(), "source: {}", source);
+    }
+}
+
+#[test]
+fn test_raw_strings() {
+    let source = String::from(r###"r"C:\new\t" r#"say "hi"\n"# raw"###);
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::StringLiteral("C:\\new\\t".to_string()),
+            Token::StringLiteral("say \"hi\"\\n".to_string()),
+            Token::Identifier("raw".to_string()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_unterminated_raw_string() {
+    let source = String::from("r#\"never closed\"");
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![Token::Error("Unterminated raw string.".to_string()), Token::EOF]
+    );
+}
+
+#[test]
+fn test_template_segments() {
+    let source = String::from("`sum: ${a + 1}, name: $name\\$`");
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Template(vec![
+                TemplatePart::Literal("sum: ".to_string()),
+                TemplatePart::Expression(vec![
+                    SpannedToken::new(Token::Identifier("a".to_string()), Span::new(8, 9, 1, 9)),
+                    SpannedToken::new(Token::Plus, Span::new(10, 11, 1, 11)),
+                    SpannedToken::new(Token::Integer(1), Span::new(12, 13, 1, 13)),
+                    SpannedToken::new(Token::EOF, Span::new(13, 13, 1, 14)),
+                ]),
+                TemplatePart::Literal(", name: ".to_string()),
+                TemplatePart::Expression(vec![
+                    SpannedToken::new(Token::Identifier("name".to_string()), Span::new(23, 27, 1, 24)),
+                    SpannedToken::new(Token::EOF, Span::new(27, 27, 1, 28)),
+                ]),
+                TemplatePart::Literal("$".to_string()),
+            ]),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_template_expression_accepts_dollar_names() {
+    let source = String::from("`${f($x)}`");
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Template(vec![TemplatePart::Expression(vec![
+                SpannedToken::new(Token::Identifier("f".to_string()), Span::new(3, 4, 1, 4)),
+                SpannedToken::new(Token::LeftParen, Span::new(4, 5, 1, 5)),
+                SpannedToken::new(Token::Identifier("x".to_string()), Span::new(6, 7, 1, 7)),
+                SpannedToken::new(Token::RightParen, Span::new(7, 8, 1, 8)),
+                SpannedToken::new(Token::EOF, Span::new(8, 8, 1, 9)),
+            ])]),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_unterminated_templates() {
+    let cases = [
+        ("`never closed", "Unterminated template string."),
+        ("`value: ${x + 1", "Unterminated template interpolation."),
+        ("`${\"oops}`", "Unterminated string."),
+    ];
+
+    for (source, message) in cases {
+        let mut lexer = Lexer::new(source.to_string());
+        let tokens = lexer.scan_tokens();
+        assert_eq!(tokens[0], Token::Error(message.to_string()), "source: {}", source);
+    }
+}
+
+#[test]
+fn test_brackets_and_colon() {
+    let mut lexer = Lexer::new("xs[0] = [1, 2:];".to_string());
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Identifier("xs".to_string()),
+            Token::LeftBracket,
+            Token::Integer(0),
+            Token::RightBracket,
+            Token::Equal,
+            Token::LeftBracket,
+            Token::Integer(1),
+            Token::Comma,
+            Token::Integer(2),
+            Token::Colon,
+            Token::RightBracket,
+            Token::Semicolon,
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_range_operators() {
+    let mut lexer = Lexer::new("for (i in 0..10) 1..=n a.b".to_string());
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::For,
+            Token::LeftParen,
+            Token::Identifier("i".to_string()),
+            Token::In,
+            Token::Integer(0),
+            Token::DotDot,
+            Token::Integer(10),
+            Token::RightParen,
+            Token::Integer(1),
+            Token::DotDotEqual,
+            Token::Identifier("n".to_string()),
+            Token::Identifier("a".to_string()),
+            Token::Dot,
+            Token::Identifier("b".to_string()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_break_and_continue_keywords() {
+    let mut lexer = Lexer::new("break; continue; breaks".to_string());
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Break,
+            Token::Semicolon,
+            Token::Continue,
+            Token::Semicolon,
+            Token::Identifier("breaks".to_string()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_compound_assignment_operators() {
+    let mut lexer = Lexer::new("a += 1 -= *= /= %= / // note".to_string());
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Identifier("a".to_string()),
+            Token::PlusEqual,
+            Token::Integer(1),
+            Token::MinusEqual,
+            Token::StarEqual,
+            Token::SlashEqual,
+            Token::PercentEqual,
+            Token::Slash,
+            Token::Comment(" note".to_string()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_fat_arrow() {
+    let mut lexer = Lexer::new("=> = == =>=".to_string());
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![Token::FatArrow, Token::Equal, Token::EqualEqual, Token::FatArrow, Token::Equal, Token::EOF]
+    );
+}
+
+#[test]
+fn test_exponent_and_bitwise_operators() {
+    let mut lexer = Lexer::new("2 ^ 3 & | ~ << >> <= >= div divide".to_string());
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Integer(2),
+            Token::Caret,
+            Token::Integer(3),
+            Token::Ampersand,
+            Token::Pipe,
+            Token::Tilde,
+            Token::LessLess,
+            Token::GreaterGreater,
+            Token::LessEqual,
+            Token::GreaterEqual,
+            Token::Div,
+            Token::Identifier("divide".to_string()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_integer_and_float_literals() {
+    let mut lexer = Lexer::new("42 4.2 3.0 1. 9223372036854775807 9223372036854775808".to_string());
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Integer(42),
+            Token::Number(4.2),
+            Token::Number(3.0),
+            Token::Integer(1),
+            Token::Dot,
+            Token::Integer(i64::MAX),
+            Token::BigInteger("9223372036854775808".to_string()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_prefixed_integer_literals() {
+    let mut lexer = Lexer::new("0xff 0XFF 0b1010 0o17 0b0 0x7fff_ffff_ffff_ffff 0xFFFF_FFFF_FFFF_FFFF".to_string());
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Integer(255),
+            Token::Integer(255),
+            Token::Integer(10),
+            Token::Integer(15),
+            Token::Integer(0),
+            Token::Integer(i64::MAX),
+            Token::BigInteger("0xffffffffffffffff".to_string()),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_digit_separators_and_scientific_notation() {
+    let mut lexer = Lexer::new("1_000_000 2.718_5 1e9 1e-9 2.5E+3 6.02e2_3 0..1e3".to_string());
+    let tokens = lexer.scan_tokens();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Integer(1_000_000),
+            Token::Number(2.7185),
+            Token::Number(1e9),
+            Token::Number(1e-9),
+            Token::Number(2500.0),
+            Token::Number(6.02e23),
+            Token::Integer(0),
+            Token::DotDot,
+            Token::Number(1000.0),
+            Token::EOF
+        ]
+    );
+}
+
+#[test]
+fn test_malformed_number_literals() {
+    let cases = [
+        ("0x", "Number literal '0x' is missing digits."),
+        ("1e", "Number literal '1e' is missing digits."),
+        ("0b102", "'2' is not a valid binary digit in number literal '0b102'."),
+        ("0o8", "'8' is not a valid octal digit in number literal '0o8'."),
+        ("0xfg", "'g' is not a valid hex digit in number literal '0xfg'."),
+        ("12px", "'p' is not a valid decimal digit in number literal '12px'."),
+        ("1_", "'_' in number literal '1_' must be between two digits."),
+        ("1__0", "'_' in number literal '1__0' must be between two digits."),
+        ("0x_1", "'_' in number literal '0x_1' must be between two digits."),
+        ("1.5_e3", "'_' in number literal '1.5_e3' must be between two digits."),
+        ("1e999", "Number literal '1e999' is too large for a float."),
+    ];
+
+    for (source, message) in cases {
+        let mut lexer = Lexer::new(source.to_string());
+        let tokens = lexer.scan_spanned_tokens();
+        assert_eq!(tokens[0].token, Token::Error(message.to_string()), "source: {}", source);
+        // The error covers the whole literal
+        assert_eq!(tokens[0].span.end, source.len(), "source: {}", source);
+    }
+
+    // A sign only belongs to the exponent when a digit follows it
+    let mut lexer = Lexer::new("1e+x".to_string());
+    assert_eq!(lexer.scan_tokens()[0], Token::Error("Number literal '1e' is missing digits.".to_string()));
+}
@@ -1,6 +1,34 @@
 use fddl::lexer::Lexer;
 use fddl::parser::Parser;
-use fddl::parser::ast::{Statement, Expression, Literal, Operator};
+use fddl::lexer::token::{Span, Token};
+use fddl::parser::error::{ParseError, ParseErrorKind};
+use fddl::parser::resolver::resolve;
+use fddl::parser::ast::{AssignTarget, Statement, StatementKind, Expression, ExpressionKind, InterpolationPart, Literal, Operator};
+
+// Builds nodes with a default span; spans don't take part in AST equality.
+fn stmt(kind: StatementKind) -> Statement {
+    kind.into()
+}
+
+fn expr(kind: ExpressionKind) -> Expression {
+    kind.into()
+}
+
+// `target = value;` (or `+=` etc. with an operator) as a statement.
+fn assignment(target: AssignTarget, operator: Option<Operator>, value: Expression) -> Statement {
+    stmt(StatementKind::ExpressionStatement(expr(ExpressionKind::Assignment {
+        target: Box::new(target),
+        operator,
+        value: Box::new(value),
+    })))
+}
+
+fn parse_errors(source: &str) -> Vec<ParseError> {
+    let mut lexer = Lexer::new(source.to_string());
+    let tokens = lexer.scan_spanned_tokens();
+    let mut parser = Parser::new(tokens);
+    parser.parse_program().expect_err("program should fail to parse")
+}
 
 #[test]
 fn test_simple_print_statement_number() {
@@ -10,12 +38,12 @@ fn test_simple_print_statement_number() {
     let tokens = lexer.scan_tokens();
 
     let mut parser = Parser::new(tokens);
-    let program_ast = parser.parse_program(); 
+    let program_ast = parser.parse_program().expect("program should parse");
 
     let expected_ast = vec![
-        Statement::PrintStatement(
-            Expression::Literal(Literal::Number(123.0))
-        )
+        stmt(StatementKind::PrintStatement(
+            expr(ExpressionKind::Literal(Literal::Int(123)))
+        ))
     ];
 
     assert_eq!(program_ast, expected_ast, "AST for 'print 123;' did not match.");
@@ -28,13 +56,13 @@ fn test_variable_declaration_with_initializer() {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.scan_tokens();
     let mut parser = Parser::new(tokens);
-    let program_ast = parser.parse_program();
+    let program_ast = parser.parse_program().expect("program should parse");
 
     let expected_ast = vec![
-        Statement::VariableDeclaration(
+        stmt(StatementKind::VariableDeclaration(
             "x".to_string(),
-            Some(Expression::Literal(Literal::Number(10.0)))
-        )
+            Some(expr(ExpressionKind::Literal(Literal::Int(10))))
+        ))
     ];
     assert_eq!(program_ast, expected_ast, "AST for 'let x = 10;' did not match.");
 }
@@ -45,15 +73,15 @@ fn test_unary_not_expression() {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.scan_tokens();
     let mut parser = Parser::new(tokens);
-    let program_ast = parser.parse_program();
+    let program_ast = parser.parse_program().expect("program should parse");
 
     let expected_ast = vec![
-        Statement::PrintStatement(
-            Expression::Unary(
+        stmt(StatementKind::PrintStatement(
+            expr(ExpressionKind::Unary(
                 Operator::Not, // Assuming Operator::Not exists from previous steps
-                Box::new(Expression::Literal(Literal::Boolean(true)))
-            )
-        )
+                Box::new(expr(ExpressionKind::Literal(Literal::Boolean(true))))
+            ))
+        ))
     ];
     assert_eq!(program_ast, expected_ast, "AST for 'print not true;' did not match.");
 }
@@ -64,39 +92,60 @@ fn test_binary_precedence() {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.scan_tokens();
     let mut parser = Parser::new(tokens);
-    let program_ast = parser.parse_program();
+    let program_ast = parser.parse_program().expect("program should parse");
 
     let expected_ast = vec![
-        Statement::PrintStatement(
-            Expression::Binary(
-                Box::new(Expression::Literal(Literal::Number(1.0))),
+        stmt(StatementKind::PrintStatement(
+            expr(ExpressionKind::Binary(
+                Box::new(expr(ExpressionKind::Literal(Literal::Int(1)))),
                 Operator::Plus,
-                Box::new(Expression::Binary(
-                    Box::new(Expression::Literal(Literal::Number(2.0))),
+                Box::new(expr(ExpressionKind::Binary(
+                    Box::new(expr(ExpressionKind::Literal(Literal::Int(2)))),
                     Operator::Multiply,
-                    Box::new(Expression::Literal(Literal::Number(3.0)))
-                ))
-            )
-        )
+                    Box::new(expr(ExpressionKind::Literal(Literal::Int(3))))
+                )))
+            ))
+        ))
     ];
     assert_eq!(program_ast, expected_ast, "AST for 'print 1 + 2 * 3;' did not match.");
 }
 
+#[test]
+fn test_almost_equal_has_equality_precedence() {
+    let mut lexer = Lexer::new("print a ~= b < c == d;".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let variable = |name: &str| Box::new(expr(ExpressionKind::Variable(name.to_string())));
+    let expected_ast = vec![
+        stmt(StatementKind::PrintStatement(expr(ExpressionKind::Binary(
+            Box::new(expr(ExpressionKind::Binary(
+                variable("a"),
+                Operator::AlmostEqual,
+                Box::new(expr(ExpressionKind::Binary(variable("b"), Operator::Less, variable("c")))),
+            ))),
+            Operator::EqualEqual,
+            variable("d"),
+        ))))
+    ];
+    assert_eq!(program_ast, expected_ast);
+}
+
 #[test]
 fn test_simple_function_call_statement() {
     let source = String::from("my_func();"); // As an expression statement
     let mut lexer = Lexer::new(source);
     let tokens = lexer.scan_tokens();
     let mut parser = Parser::new(tokens);
-    let program_ast = parser.parse_program();
+    let program_ast = parser.parse_program().expect("program should parse");
 
     let expected_ast = vec![
-        Statement::ExpressionStatement(
-            Expression::FunctionCall(
-                Box::new(Expression::Variable("my_func".to_string())),
+        stmt(StatementKind::ExpressionStatement(
+            expr(ExpressionKind::FunctionCall(
+                Box::new(expr(ExpressionKind::Variable("my_func".to_string()))),
                 Vec::new() // No arguments
-            )
-        )
+            ))
+        ))
     ];
     assert_eq!(program_ast, expected_ast, "AST for 'my_func();' did not match.");
 }
@@ -107,17 +156,18 @@ fn test_assignment_statement() {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.scan_tokens();
     let mut parser = Parser::new(tokens);
-    let program_ast = parser.parse_program();
+    let program_ast = parser.parse_program().expect("program should parse");
 
     let expected_ast = vec![
-        Statement::Assignment { // Assuming you added this variant to Statement
-            target_name: "count".to_string(),
-            value: Expression::Binary(
-                Box::new(Expression::Variable("count".to_string())),
+        assignment(
+            AssignTarget::Variable("count".to_string()),
+            None,
+            expr(ExpressionKind::Binary(
+                Box::new(expr(ExpressionKind::Variable("count".to_string()))),
                 Operator::Plus,
-                Box::new(Expression::Literal(Literal::Number(1.0)))
-            )
-        }
+                Box::new(expr(ExpressionKind::Literal(Literal::Int(1))))
+            ))
+        )
     ];
     assert_eq!(program_ast, expected_ast, "AST for 'count = count + 1;' did not match.");
 }
@@ -128,22 +178,633 @@ fn test_if_else_statement_with_blocks() {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.scan_tokens();
     let mut parser = Parser::new(tokens);
-    let program_ast = parser.parse_program();
+    let program_ast = parser.parse_program().expect("program should parse");
 
     let expected_ast = vec![
-        Statement::IfStatement(
-            Expression::Binary( // Condition: x < 10
-                Box::new(Expression::Variable("x".to_string())),
+        stmt(StatementKind::IfStatement(
+            expr(ExpressionKind::Binary( // Condition: x < 10
+                Box::new(expr(ExpressionKind::Variable("x".to_string()))),
                 Operator::Less,
-                Box::new(Expression::Literal(Literal::Number(10.0)))
-            ),
-            Box::new(Statement::Block(vec![ // Then branch
-                Statement::PrintStatement(Expression::Literal(Literal::String("small".to_string())))
-            ])),
-            Some(Box::new(Statement::Block(vec![ // Else branch
-                Statement::PrintStatement(Expression::Literal(Literal::String("large".to_string())))
-            ])))
-        )
+                Box::new(expr(ExpressionKind::Literal(Literal::Int(10))))
+            )),
+            Box::new(stmt(StatementKind::Block(vec![ // Then branch
+                stmt(StatementKind::PrintStatement(expr(ExpressionKind::Literal(Literal::String("small".to_string())))))
+            ]))),
+            Some(Box::new(stmt(StatementKind::Block(vec![ // Else branch
+                stmt(StatementKind::PrintStatement(expr(ExpressionKind::Literal(Literal::String("large".to_string())))))
+            ]))))
+        ))
     ];
     assert_eq!(program_ast, expected_ast, "AST for if-else statement did not match.");
-}
\ No newline at end of file
+}
+#[test]
+fn test_statement_and_expression_spans() {
+    let source = String::from("let x = 10;\nprint x + 2;");
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.scan_spanned_tokens();
+    let mut parser = Parser::new(tokens);
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    assert_eq!(program_ast[0].span, Span::new(0, 11, 1, 1));
+    assert_eq!(program_ast[1].span, Span::new(12, 24, 2, 1));
+
+    match &program_ast[1].kind {
+        StatementKind::PrintStatement(value) => {
+            assert_eq!(value.span, Span::new(18, 23, 2, 7));
+        }
+        other => panic!("Expected a print statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_missing_semicolon_error() {
+    let errors = parse_errors("let x = 10\nprint x;");
+
+    assert_eq!(errors.len(), 1);
+    let error = &errors[0];
+    assert_eq!(error.kind, ParseErrorKind::UnexpectedToken);
+    assert_eq!(error.expected, vec![Token::Semicolon]);
+    assert_eq!(error.found, Some(Token::Print));
+    assert_eq!(error.span, Span::new(11, 16, 2, 1));
+}
+
+#[test]
+fn test_invalid_assignment_target_error() {
+    let errors = parse_errors("1 + 2 = 3;");
+
+    assert_eq!(errors[0].kind, ParseErrorKind::InvalidAssignmentTarget);
+    assert_eq!(errors[0].span, Span::new(0, 5, 1, 1));
+}
+
+#[test]
+fn test_unexpected_eof_error() {
+    let errors = parse_errors("print (1 + ");
+
+    assert_eq!(errors[0].kind, ParseErrorKind::UnexpectedEof);
+    assert_eq!(errors[0].found, Some(Token::EOF));
+}
+
+#[test]
+fn test_lexer_error_is_reported_as_parse_error() {
+    let errors = parse_errors("let a = 1 ! 2;");
+
+    assert_eq!(errors[0].kind, ParseErrorKind::LexError);
+    assert_eq!(errors[0].message, "Unexpected character '!'");
+}
+
+#[test]
+fn test_recovery_reports_every_bad_statement() {
+    let source = "let a = ;\nprint 1\nlet b = 2;\nfunc f(1) { }\nc = = 3;\n}\nprint b;";
+    let errors = parse_errors(source);
+
+    let lines: Vec<usize> = errors.iter().map(|error| error.span.line).collect();
+    assert_eq!(lines, vec![1, 3, 4, 5, 6]);
+}
+
+#[test]
+fn test_recovery_keeps_partial_ast() {
+    let source = String::from("let a = 1;\nlet = 2;\nfunc f() { print ; print a; }\nprint a;");
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.scan_spanned_tokens();
+    let mut parser = Parser::new(tokens);
+    let (program_ast, errors) = parser.parse_program_with_recovery();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        program_ast,
+        vec![
+            stmt(StatementKind::VariableDeclaration(
+                "a".to_string(),
+                Some(expr(ExpressionKind::Literal(Literal::Int(1))))
+            )),
+            stmt(StatementKind::FunctionDeclaration {
+                name: "f".to_string(),
+                params: Vec::new(),
+                body: vec![stmt(StatementKind::PrintStatement(expr(ExpressionKind::Variable("a".to_string()))))],
+            }),
+            stmt(StatementKind::PrintStatement(expr(ExpressionKind::Variable("a".to_string())))),
+        ]
+    );
+}
+
+#[test]
+fn test_template_interpolation() {
+    let mut lexer = Lexer::new("print `x is ${x * 2}!`;".to_string());
+    let mut parser = Parser::new(lexer.scan_spanned_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    assert_eq!(
+        program_ast,
+        vec![stmt(StatementKind::PrintStatement(expr(ExpressionKind::Interpolation(vec![
+            InterpolationPart::Literal("x is ".to_string()),
+            InterpolationPart::Expression(expr(ExpressionKind::Binary(
+                Box::new(expr(ExpressionKind::Variable("x".to_string()))),
+                Operator::Multiply,
+                Box::new(expr(ExpressionKind::Literal(Literal::Int(2)))),
+            ))),
+            InterpolationPart::Literal("!".to_string()),
+        ]))))]
+    );
+}
+
+#[test]
+fn test_template_interpolation_errors() {
+    let errors = parse_errors("print `a ${} b`;");
+    assert_eq!(errors[0].message, "Expected an expression inside '${}'.");
+    assert_eq!(errors[0].found, Some(Token::RightBrace));
+    assert_eq!(errors[0].span, Span::new(11, 11, 1, 12));
+
+    let errors = parse_errors("print `a ${1 2} b`;");
+    assert_eq!(errors[0].message, "Expected '}' after interpolated expression.");
+    assert_eq!(errors[0].span, Span::new(13, 14, 1, 14));
+}
+
+#[test]
+fn test_template_interpolation_keeps_recovered_errors() {
+    // A function body recovers from its errors, but they still fail the program.
+    let errors = parse_errors("print `a ${ func () { let = ; } } b`;");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Expected variable name after 'let'.");
+
+    let errors = parse_errors("print `${ func () { break; } }`;");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ParseErrorKind::OutsideLoop);
+}
+
+fn resolve_errors(source: &str) -> Vec<ParseError> {
+    let mut lexer = Lexer::new(source.to_string());
+    let mut parser = Parser::new(lexer.scan_spanned_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+    resolve(&program_ast).err().unwrap_or_default()
+}
+
+#[test]
+fn test_const_declaration() {
+    let mut lexer = Lexer::new("const limit = 10;".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    assert_eq!(
+        program_ast,
+        vec![stmt(StatementKind::ConstDeclaration(
+            "limit".to_string(),
+            expr(ExpressionKind::Literal(Literal::Int(10))),
+        ))]
+    );
+}
+
+#[test]
+fn test_const_requires_initializer() {
+    let errors = parse_errors("const limit;");
+
+    assert_eq!(errors[0].message, "Expected '=' after constant name; constants need a value.");
+    assert_eq!(errors[0].found, Some(Token::Semicolon));
+}
+
+#[test]
+fn test_resolver_rejects_assignment_to_visible_const() {
+    let errors = resolve_errors("const a = 1;\n{\n  a = 2;\n}\nfunc f() { a = 3; }");
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|error| error.kind == ParseErrorKind::AssignToConstant));
+    assert_eq!(errors[0].message, "Cannot assign to constant 'a' (declared at line 1, column 1).");
+    assert_eq!(errors[0].span, Span::new(17, 22, 3, 3));
+    assert_eq!(errors[1].span.line, 5);
+}
+
+#[test]
+fn test_resolver_rejects_redeclaring_const_in_same_scope() {
+    let errors = resolve_errors("const a = 1;\nlet a = 2;\nfunc a() { }\n{ const b = 1; const b = 2; }");
+
+    assert_eq!(errors.len(), 3);
+    assert!(errors.iter().all(|error| error.kind == ParseErrorKind::AssignToConstant));
+    assert_eq!(errors[0].message, "Cannot redeclare constant 'a' (declared at line 1, column 1).");
+    assert_eq!(errors[0].span.line, 2);
+    assert_eq!(errors[1].span.line, 3);
+    assert_eq!(errors[2].message, "Cannot redeclare constant 'b' (declared at line 4, column 3).");
+}
+
+#[test]
+fn test_resolver_allows_shadowing_and_unknown_names() {
+    let source = "const a = 1;\n\
+                  { let a = 2; a = 3; }\n\
+                  func f(a) { a = 4; }\n\
+                  for (let a = 0; a < 3; ) { a = a + 1; }\n\
+                  b = 5;\n\
+                  let c = 1; c = 2;";
+
+    assert_eq!(resolve_errors(source), vec![]);
+}
+
+#[test]
+fn test_module_pub_and_import_statements() {
+    let mut lexer = Lexer::new("import \"lib/math.fddl\"; import util; module m { pub let x = 1; } print m.x;".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    assert_eq!(
+        program_ast,
+        vec![
+            stmt(StatementKind::Import { path: "lib/math.fddl".to_string(), name: "math".to_string() }),
+            stmt(StatementKind::Import { path: "util".to_string(), name: "util".to_string() }),
+            stmt(StatementKind::ModuleDeclaration {
+                name: "m".to_string(),
+                body: vec![stmt(StatementKind::Public(Box::new(stmt(StatementKind::VariableDeclaration(
+                    "x".to_string(),
+                    Some(expr(ExpressionKind::Literal(Literal::Int(1)))),
+                )))))],
+            }),
+            stmt(StatementKind::PrintStatement(expr(ExpressionKind::Get(
+                Box::new(expr(ExpressionKind::Variable("m".to_string()))),
+                "x".to_string(),
+            )))),
+        ]
+    );
+}
+
+#[test]
+fn test_module_syntax_errors() {
+    let errors = parse_errors("pub print 1;");
+    assert_eq!(errors[0].message, "Expected a declaration after 'pub'.");
+
+    let errors = parse_errors("import \"lib/my-math\";");
+    assert_eq!(errors[0].message, "Can't import \"lib/my-math\": its file name isn't a valid identifier.");
+
+    let errors = parse_errors("print m.;");
+    assert_eq!(errors[0].message, "Expected member name after '.'.");
+}
+
+#[test]
+fn test_resolver_rejects_pub_outside_module_level() {
+    let errors = resolve_errors("pub let a = 1;\nmodule m { pub let b = 2; { pub let c = 3; } }\nfunc f() { pub let d = 4; }");
+
+    let lines: Vec<usize> = errors.iter().map(|error| error.span.line).collect();
+    assert_eq!(lines, vec![2, 3]);
+    assert!(errors.iter().all(|error| error.kind == ParseErrorKind::MisplacedPub));
+}
+
+#[test]
+fn test_symbol_literal() {
+    let mut lexer = Lexer::new("let color = sym red;".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    assert_eq!(
+        program_ast,
+        vec![stmt(StatementKind::VariableDeclaration(
+            "color".to_string(),
+            Some(expr(ExpressionKind::Literal(Literal::Symbol("red".to_string())))),
+        ))]
+    );
+
+    let errors = parse_errors("let color = sym 1;");
+    assert_eq!(errors[0].message, "Expected a name after 'sym'.");
+}
+
+#[test]
+fn test_list_literal_index_and_index_assignment() {
+    let mut lexer = Lexer::new("let xs = [1, \"two\",]; xs[-1] = [];".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    assert_eq!(
+        program_ast,
+        vec![
+            stmt(StatementKind::VariableDeclaration(
+                "xs".to_string(),
+                Some(expr(ExpressionKind::List(vec![
+                    expr(ExpressionKind::Literal(Literal::Int(1))),
+                    expr(ExpressionKind::Literal(Literal::String("two".to_string()))),
+                ]))),
+            )),
+            assignment(
+                AssignTarget::Index {
+                    object: expr(ExpressionKind::Variable("xs".to_string())),
+                    index: expr(ExpressionKind::Unary(
+                        Operator::Minus,
+                        Box::new(expr(ExpressionKind::Literal(Literal::Int(1)))),
+                    )),
+                },
+                None,
+                expr(ExpressionKind::List(vec![])),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_list_syntax_errors() {
+    let errors = parse_errors("let xs = [1, 2;");
+    assert_eq!(errors[0].message, "Expected ']' after list elements.");
+
+    let errors = parse_errors("print xs[0;");
+    assert_eq!(errors[0].message, "Expected ']' after index.");
+}
+
+#[test]
+fn test_map_literal_and_field_assignment() {
+    let mut lexer = Lexer::new("let m = { \"a\": 1, b: { }, sym c: 3 }; m.b = 2; { x; }".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let string = |text: &str| expr(ExpressionKind::Literal(Literal::String(text.to_string())));
+    let number = |n: i64| expr(ExpressionKind::Literal(Literal::Int(n)));
+    assert_eq!(
+        program_ast,
+        vec![
+            stmt(StatementKind::VariableDeclaration(
+                "m".to_string(),
+                Some(expr(ExpressionKind::Map(vec![
+                    (string("a"), number(1)),
+                    (string("b"), expr(ExpressionKind::Map(vec![]))),
+                    (expr(ExpressionKind::Literal(Literal::Symbol("c".to_string()))), number(3)),
+                ]))),
+            )),
+            assignment(
+                AssignTarget::Field {
+                    object: expr(ExpressionKind::Variable("m".to_string())),
+                    name: "b".to_string(),
+                },
+                None,
+                number(2),
+            ),
+            // A '{' at the start of a statement is still a block.
+            stmt(StatementKind::Block(vec![stmt(StatementKind::ExpressionStatement(expr(
+                ExpressionKind::Variable("x".to_string())
+            )))])),
+        ]
+    );
+}
+
+#[test]
+fn test_map_syntax_errors() {
+    let errors = parse_errors("let m = { a 1 };");
+    assert_eq!(errors[0].message, "Expected ':' after map key.");
+
+    let errors = parse_errors("let m = { a: 1 b: 2 };");
+    assert_eq!(errors[0].message, "Expected '}' after map entries.");
+}
+
+#[test]
+fn test_for_in_statement() {
+    let mut lexer = Lexer::new("for (x in xs) { } for (k, v in m) { }".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let variable = |name: &str| expr(ExpressionKind::Variable(name.to_string()));
+    assert_eq!(
+        program_ast,
+        vec![
+            stmt(StatementKind::ForInStatement {
+                variables: vec!["x".to_string()],
+                iterable: variable("xs"),
+                body: Box::new(stmt(StatementKind::Block(vec![]))),
+            }),
+            stmt(StatementKind::ForInStatement {
+                variables: vec!["k".to_string(), "v".to_string()],
+                iterable: variable("m"),
+                body: Box::new(stmt(StatementKind::Block(vec![]))),
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_range_binds_looser_than_arithmetic() {
+    let mut lexer = Lexer::new("let r = 0..=n - 1 < 2;".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let number = |n: i64| expr(ExpressionKind::Literal(Literal::Int(n)));
+    let range = expr(ExpressionKind::Range {
+        start: Box::new(number(0)),
+        end: Box::new(expr(ExpressionKind::Binary(
+            Box::new(expr(ExpressionKind::Variable("n".to_string()))),
+            Operator::Minus,
+            Box::new(number(1)),
+        ))),
+        inclusive: true,
+    });
+    assert_eq!(
+        program_ast,
+        vec![stmt(StatementKind::VariableDeclaration(
+            "r".to_string(),
+            Some(expr(ExpressionKind::Binary(Box::new(range), Operator::Less, Box::new(number(2))))),
+        ))]
+    );
+
+    let errors = parse_errors("let r = 0..1..2;");
+    assert_eq!(errors[0].message, "Expected ';' after variable declaration.");
+    let errors = parse_errors("for (x, y z) { }");
+    assert_eq!(errors[0].message, "Expected 'in' after loop variables.");
+}
+
+#[test]
+fn test_break_and_continue_inside_loops() {
+    let mut lexer = Lexer::new("while (true) { if (x) { break; } continue; }".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    assert_eq!(
+        program_ast,
+        vec![stmt(StatementKind::WhileStatement(
+            expr(ExpressionKind::Literal(Literal::Boolean(true))),
+            Box::new(stmt(StatementKind::Block(vec![
+                stmt(StatementKind::IfStatement(
+                    expr(ExpressionKind::Variable("x".to_string())),
+                    Box::new(stmt(StatementKind::Block(vec![stmt(StatementKind::BreakStatement)]))),
+                    None,
+                )),
+                stmt(StatementKind::ContinueStatement),
+            ]))),
+        ))]
+    );
+}
+
+#[test]
+fn test_break_and_continue_outside_loops_are_errors() {
+    let errors = parse_errors("let a = 1;\nbreak;\nfor (x in xs) { func f() { continue; } }");
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind, ParseErrorKind::OutsideLoop);
+    assert_eq!(errors[0].message, "'break' can only be used inside a loop.");
+    assert_eq!(errors[0].span, Span::new(11, 17, 2, 1));
+    assert_eq!(errors[1].message, "'continue' can only be used inside a loop.");
+
+    let errors = parse_errors("while (true) { break }");
+    assert_eq!(errors[0].message, "Expected ';' after 'break'.");
+}
+
+#[test]
+fn test_compound_assignment() {
+    let mut lexer = Lexer::new("xs[0] += 2; for (; i < 3; i *= 2) { } a = b -= 1;".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let variable = |name: &str| expr(ExpressionKind::Variable(name.to_string()));
+    let number = |n: i64| expr(ExpressionKind::Literal(Literal::Int(n)));
+    let assign = |target: AssignTarget, operator: Option<Operator>, value: Expression| {
+        expr(ExpressionKind::Assignment { target: Box::new(target), operator, value: Box::new(value) })
+    };
+    assert_eq!(
+        program_ast,
+        vec![
+            stmt(StatementKind::ExpressionStatement(assign(
+                AssignTarget::Index { object: variable("xs"), index: number(0) },
+                Some(Operator::Plus),
+                number(2),
+            ))),
+            stmt(StatementKind::ForStatement(
+                Box::new(stmt(StatementKind::ExpressionStatement(expr(ExpressionKind::Literal(Literal::Nil))))),
+                expr(ExpressionKind::Binary(Box::new(variable("i")), Operator::Less, Box::new(number(3)))),
+                Box::new(stmt(StatementKind::ExpressionStatement(assign(
+                    AssignTarget::Variable("i".to_string()),
+                    Some(Operator::Multiply),
+                    number(2),
+                )))),
+                Box::new(stmt(StatementKind::Block(vec![]))),
+            )),
+            // Assignment is right-associative.
+            assignment(
+                AssignTarget::Variable("a".to_string()),
+                None,
+                assign(AssignTarget::Variable("b".to_string()), Some(Operator::Minus), number(1)),
+            ),
+        ]
+    );
+
+    let errors = parse_errors("f() += 1;");
+    assert_eq!(errors[0].kind, ParseErrorKind::InvalidAssignmentTarget);
+}
+
+#[test]
+fn test_resolver_rejects_compound_assignment_to_const() {
+    let errors = resolve_errors("const c = 1; c += 1; for (; c < 3; c = c + 1) { }");
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind, ParseErrorKind::AssignToConstant);
+    assert_eq!(errors[0].span, Span::new(13, 19, 1, 14));
+    assert_eq!(errors[1].span, Span::new(35, 44, 1, 36));
+}
+
+#[test]
+fn test_expression_bodied_and_anonymous_functions() {
+    let mut lexer = Lexer::new("func double(x) => x * 2; let f = func (a, b) { return a; }; apply(func () => nil);".to_string());
+    let mut parser = Parser::new(lexer.scan_tokens());
+    let program_ast = parser.parse_program().expect("program should parse");
+
+    let variable = |name: &str| expr(ExpressionKind::Variable(name.to_string()));
+    assert_eq!(
+        program_ast,
+        vec![
+            // `=> expression` is shorthand for a body that returns it.
+            stmt(StatementKind::FunctionDeclaration {
+                name: "double".to_string(),
+                params: vec!["x".to_string()],
+                body: vec![stmt(StatementKind::ReturnStatement(Some(expr(ExpressionKind::Binary(
+                    Box::new(variable("x")),
+                    Operator::Multiply,
+                    Box::new(expr(ExpressionKind::Literal(Literal::Int(2)))),
+                )))))],
+            }),
+            stmt(StatementKind::VariableDeclaration(
+                "f".to_string(),
+                Some(expr(ExpressionKind::Function {
+                    params: vec!["a".to_string(), "b".to_string()],
+                    body: vec![stmt(StatementKind::ReturnStatement(Some(variable("a"))))],
+                })),
+            )),
+            stmt(StatementKind::ExpressionStatement(expr(ExpressionKind::FunctionCall(
+                Box::new(variable("apply")),
+                vec![expr(ExpressionKind::Function {
+                    params: vec![],
+                    body: vec![stmt(StatementKind::ReturnStatement(Some(expr(ExpressionKind::Literal(Literal::Nil)))))],
+                })],
+            )))),
+        ]
+    );
+}
+
+#[test]
+fn test_function_body_errors() {
+    let errors = parse_errors("func square(x) => x * x");
+    assert_eq!(errors[0].message, "Expected ';' after expression body of function 'square'.");
+
+    let errors = parse_errors("let f = func (x) x;");
+    assert_eq!(errors[0].message, "Expected '{' or '=>' for function body.");
+    assert_eq!(errors[0].expected, vec![Token::LeftBrace, Token::FatArrow]);
+
+    let errors = parse_errors("while (true) { let f = func () { break; }; }");
+    assert_eq!(errors[0].kind, ParseErrorKind::OutsideLoop);
+}
+
+#[test]
+fn test_exponent_precedence() {
+    let number = |n: i64| expr(ExpressionKind::Literal(Literal::Int(n)));
+    let binary = |left: Expression, operator: Operator, right: Expression| {
+        expr(ExpressionKind::Binary(Box::new(left), operator, Box::new(right)))
+    };
+    let unary = |operator: Operator, operand: Expression| expr(ExpressionKind::Unary(operator, Box::new(operand)));
+    let print = |source: &str| {
+        let mut lexer = Lexer::new(format!("print {};", source));
+        let mut parser = Parser::new(lexer.scan_tokens());
+        match parser.parse_program().expect("program should parse").remove(0).kind {
+            StatementKind::PrintStatement(value) => value,
+            other => panic!("expected a print statement, got {:?}", other),
+        }
+    };
+
+    // Tighter than unary minus, on either side
+    assert_eq!(print("-2 ^ 2"), unary(Operator::Minus, binary(number(2), Operator::Power, number(2))));
+    assert_eq!(print("2 ^ -1"), binary(number(2), Operator::Power, unary(Operator::Minus, number(1))));
+    // Right-associative
+    assert_eq!(
+        print("2 ^ 3 ^ 2"),
+        binary(number(2), Operator::Power, binary(number(3), Operator::Power, number(2)))
+    );
+    // Tighter than '*' and 'div'
+    assert_eq!(
+        print("3 div 2 ^ 2"),
+        binary(number(3), Operator::IntegerDivide, binary(number(2), Operator::Power, number(2)))
+    );
+}
+
+#[test]
+fn test_bitwise_precedence() {
+    let number = |n: i64| expr(ExpressionKind::Literal(Literal::Int(n)));
+    let binary = |left: Expression, operator: Operator, right: Expression| {
+        expr(ExpressionKind::Binary(Box::new(left), operator, Box::new(right)))
+    };
+    let print = |source: &str| {
+        let mut lexer = Lexer::new(format!("print {};", source));
+        let mut parser = Parser::new(lexer.scan_tokens());
+        match parser.parse_program().expect("program should parse").remove(0).kind {
+            StatementKind::PrintStatement(value) => value,
+            other => panic!("expected a print statement, got {:?}", other),
+        }
+    };
+
+    // | is looser than ~, which is looser than &, which is looser than shifts
+    assert_eq!(
+        print("1 | 2 ~ 3 & 4 << 5"),
+        binary(
+            number(1),
+            Operator::BitOr,
+            binary(number(2), Operator::BitXor, binary(number(3), Operator::BitAnd, binary(number(4), Operator::ShiftLeft, number(5)))),
+        )
+    );
+    // Shifts are looser than arithmetic, and all of them are tighter than comparison
+    assert_eq!(
+        print("1 << 2 + 3 == 4 & 5"),
+        binary(
+            binary(number(1), Operator::ShiftLeft, binary(number(2), Operator::Plus, number(3))),
+            Operator::EqualEqual,
+            binary(number(4), Operator::BitAnd, number(5)),
+        )
+    );
+    // Prefix '~' is still the "almost" operator
+    assert_eq!(
+        print("~1 ~ 2"),
+        binary(expr(ExpressionKind::Unary(Operator::Almost, Box::new(number(1)))), Operator::BitXor, number(2))
+    );
+}